@@ -0,0 +1,208 @@
+use std::convert::TryFrom;
+
+use crate::common::uint::{U192, U256};
+use crate::common::*;
+use crate::decimal::*;
+use crate::error::*;
+
+/// Exact sum of decimals, accumulated in 256 bits and only narrowed when read
+///
+/// Tracks both the plain sum, for `sum` and `mean`, and the weight-scaled sum,
+/// for `weighted_mean`. Values pushed without a weight count with a weight of one,
+/// which is only applied when reading so they keep the full 256 bits of headroom.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DecimalAccumulator {
+    sum: U256,
+    count: u64,
+    unit_sum: U256,
+    unit_count: u64,
+    weighted_sum: U256,
+    weight_sum: U256,
+}
+
+impl DecimalAccumulator {
+    /// Empty accumulator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a value with a weight of one
+    pub fn try_push(&mut self, value: Decimal) -> Result<(), DecimalError> {
+        let mut next = *self;
+        next.try_add_value(value)?;
+        next.unit_sum = next
+            .unit_sum
+            .checked_add(U256::from(value.0))
+            .ok_or(DecimalError::MathOverflow)?;
+        next.unit_count += 1;
+        *self = next;
+        Ok(())
+    }
+
+    /// Add a value with the given weight
+    pub fn try_push_weighted(
+        &mut self,
+        value: Decimal,
+        weight: Decimal,
+    ) -> Result<(), DecimalError> {
+        let weighted = U256::from(value.0)
+            .checked_mul(U256::from(weight.0))
+            .ok_or(DecimalError::MathOverflow)?;
+        let mut next = *self;
+        next.try_add_value(value)?;
+        next.weighted_sum = next
+            .weighted_sum
+            .checked_add(weighted)
+            .ok_or(DecimalError::MathOverflow)?;
+        next.weight_sum = next
+            .weight_sum
+            .checked_add(U256::from(weight.0))
+            .ok_or(DecimalError::MathOverflow)?;
+        *self = next;
+        Ok(())
+    }
+
+    /// Combine with another accumulator, e.g. one per vault
+    pub fn try_merge(&mut self, other: &Self) -> Result<(), DecimalError> {
+        let add = |a: U256, b: U256| a.checked_add(b).ok_or(DecimalError::MathOverflow);
+        let add_count = |a: u64, b: u64| a.checked_add(b).ok_or(DecimalError::MathOverflow);
+        *self = Self {
+            sum: add(self.sum, other.sum)?,
+            count: add_count(self.count, other.count)?,
+            unit_sum: add(self.unit_sum, other.unit_sum)?,
+            unit_count: add_count(self.unit_count, other.unit_count)?,
+            weighted_sum: add(self.weighted_sum, other.weighted_sum)?,
+            weight_sum: add(self.weight_sum, other.weight_sum)?,
+        };
+        Ok(())
+    }
+
+    /// Number of values pushed
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Sum of the values, return an error if it doesn't fit a `Decimal`
+    pub fn sum(&self) -> Result<Decimal, DecimalError> {
+        Ok(Decimal(U192::try_from(self.sum)?))
+    }
+
+    /// Arithmetic mean of the values, return an error if no value was pushed
+    pub fn mean(&self, mode: RoundingMode) -> Result<Decimal, DecimalError> {
+        if self.count == 0 {
+            return Err(DecimalError::InvalidInput);
+        }
+        let mean = self
+            .sum
+            .checked_div_rounded(U256::from(self.count), mode)
+            .ok_or(DecimalError::MathOverflow)?;
+        Ok(Decimal(U192::try_from(mean)?))
+    }
+
+    /// Mean of the values weighted by their weights, return an error if no value was pushed
+    pub fn weighted_mean(&self, mode: RoundingMode) -> Result<Decimal, DecimalError> {
+        if self.count == 0 {
+            return Err(DecimalError::InvalidInput);
+        }
+        // Without explicit weights the unit values don't need scaling
+        let (weighted_sum, weight_sum) = if self.weight_sum.is_zero() {
+            (self.unit_sum, U256::from(self.unit_count))
+        } else {
+            let wad = U256::from(WAD);
+            let mul = |a: U256, b: U256| a.checked_mul(b).ok_or(DecimalError::MathOverflow);
+            let add = |a: U256, b: U256| a.checked_add(b).ok_or(DecimalError::MathOverflow);
+            (
+                add(self.weighted_sum, mul(self.unit_sum, wad)?)?,
+                add(self.weight_sum, mul(U256::from(self.unit_count), wad)?)?,
+            )
+        };
+        let mean = weighted_sum
+            .checked_div_rounded(weight_sum, mode)
+            .ok_or(DecimalError::MathOverflow)?;
+        Ok(Decimal(U192::try_from(mean)?))
+    }
+
+    /// Add a value to the plain sum
+    fn try_add_value(&mut self, value: Decimal) -> Result<(), DecimalError> {
+        self.sum = self
+            .sum
+            .checked_add(U256::from(value.0))
+            .ok_or(DecimalError::MathOverflow)?;
+        self.count = self
+            .count
+            .checked_add(1)
+            .ok_or(DecimalError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sum_beyond_decimal_range() {
+        let half_max = Decimal(U192::MAX / 2 + 1);
+        let mut acc = DecimalAccumulator::new();
+        acc.try_push(half_max).unwrap();
+        acc.try_push(half_max).unwrap();
+        acc.try_push(Decimal::try_from_int(4u64).unwrap()).unwrap();
+        assert_eq!(acc.count(), 3);
+        assert_eq!(acc.sum(), Err(DecimalError::MathOverflow));
+        let mean = acc.mean(RoundingMode::Down).unwrap();
+        assert!(mean.approx_eq_bps(Decimal(U192::MAX / 3), 1));
+    }
+
+    #[test]
+    fn test_mean_and_weighted_mean() {
+        let mut acc = DecimalAccumulator::new();
+        assert_eq!(
+            acc.mean(RoundingMode::Down),
+            Err(DecimalError::InvalidInput)
+        );
+        acc.try_push_weighted(
+            Decimal::try_from_int(10u64).unwrap(),
+            Decimal::try_from_int(3u64).unwrap(),
+        )
+        .unwrap();
+        acc.try_push_weighted(Decimal::try_from_int(20u64).unwrap(), Decimal::one())
+            .unwrap();
+        assert_eq!(acc.sum(), Ok(Decimal::try_from_int(30u64).unwrap()));
+        assert_eq!(
+            acc.mean(RoundingMode::Down),
+            Ok(Decimal::try_from_int(15u64).unwrap())
+        );
+        assert_eq!(
+            acc.weighted_mean(RoundingMode::Down),
+            "12.5".parse::<Decimal>()
+        );
+
+        let mut other = DecimalAccumulator::new();
+        other
+            .try_push(Decimal::try_from_int(60u64).unwrap())
+            .unwrap();
+        acc.try_merge(&other).unwrap();
+        assert_eq!(acc.count(), 3);
+        assert_eq!(
+            acc.mean(RoundingMode::Down),
+            Ok(Decimal::try_from_int(30u64).unwrap())
+        );
+        assert_eq!(
+            acc.weighted_mean(RoundingMode::Down),
+            Ok(Decimal::try_from_int(22u64).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_push_far_beyond_decimal_range() {
+        let max = Decimal(U192::MAX);
+        let mut acc = DecimalAccumulator::new();
+        // 2^12 values of 2^192 - 1 sum to about 2^204
+        for _ in 0..4_096 {
+            acc.try_push(max).unwrap();
+        }
+        assert_eq!(acc.sum(), Err(DecimalError::MathOverflow));
+        assert_eq!(acc.mean(RoundingMode::Down), Ok(max));
+        assert_eq!(acc.weighted_mean(RoundingMode::Down), Ok(max));
+    }
+}
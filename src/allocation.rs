@@ -0,0 +1,245 @@
+use std::convert::TryFrom;
+
+use crate::common::uint::{U192, U256};
+use crate::decimal::*;
+use crate::error::*;
+use crate::rate::*;
+use crate::ratio::*;
+
+/// Recipient of the dust left after rounding each part down
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemainderPolicy {
+    /// One unit each to the parts with the largest dropped fractions, ties to the earlier part
+    LargestRemainder,
+    /// All dust to the first part
+    First,
+    /// All dust to the part at this index
+    Sink(usize),
+}
+
+/// Value usable as an allocation weight, only the proportions between weights matter
+pub trait Weight {
+    /// Weight as a raw integer, scaled types use their scaled value
+    fn to_raw_weight(&self) -> U256;
+}
+
+impl Weight for u64 {
+    fn to_raw_weight(&self) -> U256 {
+        U256::from(*self)
+    }
+}
+
+impl Weight for u128 {
+    fn to_raw_weight(&self) -> U256 {
+        U256::from(*self)
+    }
+}
+
+impl Weight for Rate {
+    fn to_raw_weight(&self) -> U256 {
+        U256::from(U192::from(self.0))
+    }
+}
+
+impl Weight for Decimal {
+    fn to_raw_weight(&self) -> U256 {
+        U256::from(self.0)
+    }
+}
+
+/// Split `total` in proportion to `weights`, the parts always sum to `total`
+///
+/// Return an error on empty or all-zero weights and on a `Sink` index out of bounds.
+pub fn allocate<W: Weight>(
+    total: u64,
+    weights: &[W],
+    policy: RemainderPolicy,
+) -> Result<Vec<u64>, DecimalError> {
+    let weights: Vec<U256> = weights.iter().map(Weight::to_raw_weight).collect();
+    allocate_raw(U256::from(total), &weights, policy)?
+        .into_iter()
+        .map(|part| u64::try_from(part).map_err(|_| DecimalError::MathOverflow))
+        .collect()
+}
+
+/// Split a decimal in proportion to `weights`, the parts always sum to `total`
+///
+/// Dust is distributed in units of the last decimal digit.
+pub fn allocate_decimal<W: Weight>(
+    total: Decimal,
+    weights: &[W],
+    policy: RemainderPolicy,
+) -> Result<Vec<Decimal>, DecimalError> {
+    let weights: Vec<U256> = weights.iter().map(Weight::to_raw_weight).collect();
+    allocate_raw(U256::from(total.0), &weights, policy)?
+        .into_iter()
+        .map(|part| Ok(Decimal(U192::try_from(part)?)))
+        .collect()
+}
+
+/// Split `total` in proportion to `ratios`, the parts always sum to `total`
+///
+/// The ratios are brought to their least common denominator, so they don't need to sum to one.
+pub fn allocate_by_ratios(
+    total: u64,
+    ratios: &[Ratio],
+    policy: RemainderPolicy,
+) -> Result<Vec<u64>, DecimalError> {
+    let mut denominator = U256::one();
+    for ratio in ratios {
+        if ratio.denominator == 0 {
+            return Err(DecimalError::InvalidInput);
+        }
+        let den = U256::from(ratio.denominator);
+        denominator = (denominator / denominator.gcd(den))
+            .checked_mul(den)
+            .ok_or(DecimalError::MathOverflow)?;
+    }
+    let weights = ratios
+        .iter()
+        .map(|ratio| {
+            U256::from(ratio.numerator)
+                .checked_mul(denominator / U256::from(ratio.denominator))
+                .ok_or(DecimalError::MathOverflow)
+        })
+        .collect::<Result<Vec<U256>, DecimalError>>()?;
+    allocate_raw(U256::from(total), &weights, policy)?
+        .into_iter()
+        .map(|part| u64::try_from(part).map_err(|_| DecimalError::MathOverflow))
+        .collect()
+}
+
+/// Round each part down, then hand out the dust according to `policy`
+fn allocate_raw(
+    total: U256,
+    weights: &[U256],
+    policy: RemainderPolicy,
+) -> Result<Vec<U256>, DecimalError> {
+    if let RemainderPolicy::Sink(index) = policy {
+        if index >= weights.len() {
+            return Err(DecimalError::InvalidInput);
+        }
+    }
+    let weight_sum = weights.iter().try_fold(U256::zero(), |sum, weight| {
+        sum.checked_add(*weight).ok_or(DecimalError::MathOverflow)
+    })?;
+    if weight_sum.is_zero() {
+        return Err(DecimalError::InvalidInput);
+    }
+
+    let mut parts = Vec::with_capacity(weights.len());
+    let mut remainders = Vec::with_capacity(weights.len());
+    for weight in weights {
+        let product = total
+            .checked_mul(*weight)
+            .ok_or(DecimalError::MathOverflow)?;
+        parts.push(product / weight_sum);
+        remainders.push(product % weight_sum);
+    }
+    let dust = parts.iter().fold(total, |rest, part| rest - *part);
+
+    match policy {
+        RemainderPolicy::LargestRemainder => {
+            let mut order: Vec<usize> = (0..parts.len()).collect();
+            order.sort_by(|a, b| remainders[*b].cmp(&remainders[*a]));
+            // the dust is smaller than the number of parts, so it fits a usize
+            for index in order.into_iter().take(dust.low_u64() as usize) {
+                parts[index] += U256::one();
+            }
+        }
+        RemainderPolicy::First => parts[0] += dust,
+        RemainderPolicy::Sink(index) => parts[index] += dust,
+    }
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::*;
+
+    #[test]
+    fn test_allocate_largest_remainder() {
+        let parts = allocate(100, &[1u64, 1, 1], RemainderPolicy::LargestRemainder).unwrap();
+        assert_eq!(parts, vec![34, 33, 33]);
+        let parts = allocate(10, &[1u64, 2, 3], RemainderPolicy::LargestRemainder).unwrap();
+        // exact shares are 1.67, 3.33 and 5
+        assert_eq!(parts, vec![2, 3, 5]);
+        let parts = allocate(7, &[0u64, 5, 5], RemainderPolicy::LargestRemainder).unwrap();
+        assert_eq!(parts, vec![0, 4, 3]);
+    }
+
+    #[test]
+    fn test_allocate_policies() {
+        assert_eq!(
+            allocate(100, &[1u128, 1, 1], RemainderPolicy::First),
+            Ok(vec![34, 33, 33])
+        );
+        assert_eq!(
+            allocate(101, &[1u128, 1, 1], RemainderPolicy::Sink(2)),
+            Ok(vec![33, 33, 35])
+        );
+        assert_eq!(
+            allocate(u64::MAX, &[u64::MAX, 1], RemainderPolicy::First)
+                .map(|p| p.iter().sum::<u64>()),
+            Ok(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_allocate_invalid() {
+        let empty: [u64; 0] = [];
+        assert_eq!(
+            allocate(1, &empty, RemainderPolicy::First),
+            Err(DecimalError::InvalidInput)
+        );
+        assert_eq!(
+            allocate(1, &[0u64, 0], RemainderPolicy::First),
+            Err(DecimalError::InvalidInput)
+        );
+        assert_eq!(
+            allocate(1, &[1u64], RemainderPolicy::Sink(1)),
+            Err(DecimalError::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn test_allocate_decimal() {
+        let weights = [Rate::from_scaled_val(1u64), Rate::from_scaled_val(2u64)];
+        let parts =
+            allocate_decimal(Decimal::one(), &weights, RemainderPolicy::LargestRemainder).unwrap();
+        assert_eq!(parts[0].to_string(), "0.333333333333333333");
+        assert_eq!(parts[1].to_string(), "0.666666666666666667");
+        assert_eq!(parts[0].try_add(parts[1]), Ok(Decimal::one()));
+    }
+
+    #[test]
+    fn test_allocate_by_ratios() {
+        let split = [Ratio::new(1, 3), Ratio::new(2, 3)];
+        assert_eq!(
+            allocate_by_ratios(1_000, &split, RemainderPolicy::LargestRemainder),
+            Ok(vec![333, 667])
+        );
+        let split = [Ratio::new(1, 2), Ratio::new(1, 3), Ratio::new(1, 6)];
+        assert_eq!(
+            allocate_by_ratios(12, &split, RemainderPolicy::First),
+            Ok(vec![6, 4, 2])
+        );
+        assert_eq!(
+            allocate_by_ratios(12, &[Ratio::new(1, 0)], RemainderPolicy::First),
+            Err(DecimalError::InvalidInput)
+        );
+        // the common denominator fits 256 bits but the scaled first weight doesn't
+        let split = [
+            Ratio::new(u64::MAX, 1),
+            Ratio::new(1, (1 << 62) + 1),
+            Ratio::new(1, (1 << 62) + 3),
+            Ratio::new(1, (1 << 62) + 5),
+            Ratio::new(1, (1 << 62) + 7),
+        ];
+        assert_eq!(
+            allocate_by_ratios(12, &split, RemainderPolicy::First),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+}
@@ -19,10 +19,16 @@ pub const BPS_SCALER: u64 = PERCENT_SCALER / 100;
 #[allow(clippy::ptr_offset_with_cast)]
 #[allow(clippy::reversed_empty_ranges)]
 #[allow(clippy::manual_range_contains)]
+#[allow(clippy::manual_div_ceil)]
 pub mod uint {
     use super::*;
     use ::uint::construct_uint;
 
+    construct_uint! {
+        /// U256 with 256 bits consisting of 4 x 64-bit words
+        pub struct U256(4);
+    }
+
     construct_uint! {
         /// U192 with 192 bits consisting of 3 x 64-bit words
         pub struct U192(3);
@@ -33,6 +39,86 @@ pub mod uint {
         pub struct U128(2);
     }
 
+    /// Implement division with a rounding mode for the uint types
+    macro_rules! impl_div_rounded {
+        ($($name:ident),*) => {
+            $(
+                impl $name {
+                    /// Divide and round the quotient, return `None` on overflow or divide by zero
+                    pub fn checked_div_rounded(self, rhs: Self, mode: RoundingMode) -> Option<Self> {
+                        let quotient = self.checked_div(rhs)?;
+                        let remainder = self % rhs;
+                        let round_up = match mode {
+                            RoundingMode::Down => false,
+                            RoundingMode::Up => !remainder.is_zero(),
+                            RoundingMode::HalfUp => remainder >= rhs - remainder,
+                            RoundingMode::HalfEven => {
+                                let half = rhs - remainder;
+                                remainder > half || (remainder == half && quotient.bit(0))
+                            }
+                        };
+                        if round_up {
+                            quotient.checked_add(Self::one())
+                        } else {
+                            Some(quotient)
+                        }
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_div_rounded!(U128, U192, U256);
+
+    /// Implement the greatest common divisor for the uint types
+    macro_rules! impl_gcd {
+        ($($name:ident),*) => {
+            $(
+                impl $name {
+                    /// Greatest common divisor, zero only if both values are zero
+                    pub fn gcd(self, other: Self) -> Self {
+                        let (mut a, mut b) = (self, other);
+                        while !b.is_zero() {
+                            let rem = a % b;
+                            a = b;
+                            b = rem;
+                        }
+                        a
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_gcd!(U128, U192, U256);
+
+    impl From<U192> for U256 {
+        fn from(value: U192) -> U256 {
+            let U192(ref arr) = value;
+            let mut ret = [0; 4];
+            ret[0] = arr[0];
+            ret[1] = arr[1];
+            ret[2] = arr[2];
+            U256(ret)
+        }
+    }
+
+    impl TryFrom<U256> for U192 {
+        type Error = DecimalError;
+
+        fn try_from(value: U256) -> Result<U192, DecimalError> {
+            let U256(ref arr) = value;
+            if arr[3] != 0 {
+                return Err(DecimalError::MathOverflow);
+            }
+            let mut ret = [0; 3];
+            ret[0] = arr[0];
+            ret[1] = arr[1];
+            ret[2] = arr[2];
+            Ok(U192(ret))
+        }
+    }
+
     impl From<U128> for U192 {
         fn from(value: U128) -> U192 {
             let U128(ref arr) = value;
@@ -59,26 +145,123 @@ pub mod uint {
     }
 }
 
+/// Rounding strategy for operations that drop digits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round toward zero
+    Down,
+    /// Round away from zero
+    Up,
+    /// Round to nearest, ties away from zero
+    HalfUp,
+    /// Round to nearest, ties to even
+    HalfEven,
+}
+
+/// Unsigned integer accepted by the fallible constructors
+///
+/// Sealed so that signed integers, whose `uint` conversions panic on negative values,
+/// can't be passed.
+///
+/// ```compile_fail
+/// decimal_wad::decimal::Decimal::try_from_int(-1i64);
+/// ```
+///
+/// ```compile_fail
+/// decimal_wad::rate::Rate::try_from_percent(-5i32);
+/// ```
+///
+/// ```compile_fail
+/// decimal_wad::rate::Rate::try_from_bps(-25i64);
+/// ```
+pub trait UnsignedInt: private::Sealed + Copy {
+    /// Widen to 192 bits
+    fn to_u192(self) -> uint::U192;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_unsigned_int {
+    ($($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl UnsignedInt for $t {
+                fn to_u192(self) -> uint::U192 {
+                    uint::U192::from(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned_int!(u8, u16, u32, u64, u128, usize, uint::U128, uint::U192);
+
 /// Try to subtract, return an error on underflow
-pub trait TrySub: Sized {
+pub trait TrySub<RHS = Self>: Sized {
+    /// Result of the subtraction
+    type Output;
     /// Subtract
-    fn try_sub(self, rhs: Self) -> Result<Self, DecimalError>;
+    fn try_sub(self, rhs: RHS) -> Result<Self::Output, DecimalError>;
 }
 
 /// Try to subtract, return an error on overflow
-pub trait TryAdd: Sized {
+pub trait TryAdd<RHS = Self>: Sized {
+    /// Result of the addition
+    type Output;
     /// Add
-    fn try_add(self, rhs: Self) -> Result<Self, DecimalError>;
+    fn try_add(self, rhs: RHS) -> Result<Self::Output, DecimalError>;
 }
 
 /// Try to divide, return an error on overflow or divide by zero
-pub trait TryDiv<RHS>: Sized {
+pub trait TryDiv<RHS = Self>: Sized {
+    /// Result of the division
+    type Output;
     /// Divide
-    fn try_div(self, rhs: RHS) -> Result<Self, DecimalError>;
+    fn try_div(self, rhs: RHS) -> Result<Self::Output, DecimalError>;
 }
 
 /// Try to multiply, return an error on overflow
-pub trait TryMul<RHS>: Sized {
+pub trait TryMul<RHS = Self>: Sized {
+    /// Result of the multiplication
+    type Output;
+    /// Multiply
+    fn try_mul(self, rhs: RHS) -> Result<Self::Output, DecimalError>;
+}
+
+/// Try to compute the remainder, return an error on divide by zero
+pub trait TryRem<RHS = Self>: Sized {
+    /// Result of the remainder
+    type Output;
+    /// Remainder
+    fn try_rem(self, rhs: RHS) -> Result<Self::Output, DecimalError>;
+}
+
+/// Try to sum the items of an iterator, return an error on overflow
+pub trait TrySum<A = Self>: Sized {
+    /// Sum
+    fn try_sum<I: Iterator<Item = A>>(iter: I) -> Result<Self, DecimalError>;
+}
+
+/// Try to multiply the items of an iterator, return an error on overflow
+pub trait TryProduct<A = Self>: Sized {
     /// Multiply
-    fn try_mul(self, rhs: RHS) -> Result<Self, DecimalError>;
+    fn try_product<I: Iterator<Item = A>>(iter: I) -> Result<Self, DecimalError>;
 }
+
+/// Fallible `sum` and `product` for any iterator
+pub trait TryIterator: Iterator + Sized {
+    /// Sum the items, return an error on overflow
+    fn try_sum<S: TrySum<Self::Item>>(self) -> Result<S, DecimalError> {
+        S::try_sum(self)
+    }
+
+    /// Multiply the items, return an error on overflow
+    fn try_product<P: TryProduct<Self::Item>>(self) -> Result<P, DecimalError> {
+        P::try_product(self)
+    }
+}
+
+impl<I: Iterator> TryIterator for I {}
@@ -1,11 +1,23 @@
-use std::{convert::TryFrom, fmt};
+use std::{cmp::Ordering, convert::TryFrom, fmt, str::FromStr};
 
 use crate::common::*;
+use crate::display::*;
 use crate::error::*;
 use crate::rate::*;
+use crate::ratio::*;
 
 // Re-export for compatibility with pre 0.1.7 versions
 pub use crate::common::uint::U192;
+use crate::common::uint::U256;
+
+/// Direction of a change between two values, zero changes are reported as `Up`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// The value grew or stayed the same
+    Up,
+    /// The value shrank
+    Down,
+}
 
 /// Large decimal values, precise to 18 digits
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Eq, Ord)]
@@ -32,7 +44,20 @@ impl Decimal {
         U192::from(HALF_WAD)
     }
 
+    /// Create scaled decimal from integer value, return an error on overflow
+    ///
+    /// Accepts any unsigned integer width, including `u128`, `U128` and `U192`
+    pub fn try_from_int(val: impl UnsignedInt) -> Result<Self, DecimalError> {
+        let val = val.to_u192();
+        Ok(Self(
+            Self::wad()
+                .checked_mul(val)
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+
     /// Create scaled decimal from percent value
+    #[cfg(not(feature = "strict"))]
     pub fn from_percent<T>(percent: T) -> Self
     where
         T: Into<U192>,
@@ -41,6 +66,24 @@ impl Decimal {
         Self(percent.checked_mul(PERCENT_SCALER.into()).unwrap())
     }
 
+    /// Create scaled decimal from percent value, return an error on overflow
+    pub fn try_from_percent(percent: impl UnsignedInt) -> Result<Self, DecimalError> {
+        let percent = percent.to_u192();
+        Ok(Self(
+            percent
+                .checked_mul(PERCENT_SCALER.into())
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+
+    /// Create scaled decimal from a fractional percent value, e.g. 12.5%
+    ///
+    /// Return an error if the result can't be represented with 18 digits
+    pub fn try_from_percent_decimal(percent: Decimal) -> Result<Self, DecimalError> {
+        Self::try_unscale(percent, 100)
+    }
+
+    #[cfg(not(feature = "strict"))]
     #[deprecated(
         since = "0.1.7",
         note = "please use the generic `from_percent` instead"
@@ -65,12 +108,57 @@ impl Decimal {
         T::try_from(self.0 / BPS_SCALER).map_err(|_| DecimalError::MathOverflow)
     }
 
+    /// Get percent value from a scaled decimal, keeping the fractional digits
+    pub fn to_percent_decimal(&self) -> Result<Decimal, DecimalError> {
+        self.try_mul(100u64)
+    }
+
+    /// Get bps value from a scaled decimal, keeping the fractional digits
+    pub fn to_bps_decimal(&self) -> Result<Decimal, DecimalError> {
+        self.try_mul(10_000u64)
+    }
+
+    /// Display as a percentage rounded to `decimals` places, e.g. "5.25%"
+    pub fn display_percent(&self, decimals: usize) -> DisplayPercent {
+        DisplayPercent::new(self.0, decimals)
+    }
+
+    /// Display in bps, e.g. "525 bps"
+    pub fn display_bps(&self) -> DisplayBps {
+        DisplayBps::new(self.0)
+    }
+
     /// Create scaled decimal from bps value
+    #[cfg(not(feature = "strict"))]
     pub fn from_bps(bps: impl Into<U192>) -> Self {
         let bps: U192 = bps.into();
         Self(bps.checked_mul(BPS_SCALER.into()).unwrap())
     }
 
+    /// Create scaled decimal from bps value, return an error on overflow
+    pub fn try_from_bps(bps: impl UnsignedInt) -> Result<Self, DecimalError> {
+        let bps = bps.to_u192();
+        Ok(Self(
+            bps.checked_mul(BPS_SCALER.into())
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+
+    /// Create scaled decimal from a fractional bps value, e.g. 2.5 bps
+    ///
+    /// Return an error if the result can't be represented with 18 digits
+    pub fn try_from_bps_decimal(bps: Decimal) -> Result<Self, DecimalError> {
+        Self::try_unscale(bps, 10_000)
+    }
+
+    fn try_unscale(value: Decimal, unit: u64) -> Result<Self, DecimalError> {
+        let unit = U192::from(unit);
+        if !(value.0 % unit).is_zero() {
+            return Err(DecimalError::PrecisionLoss);
+        }
+        Ok(Self(value.0 / unit))
+    }
+
     /// Return raw scaled value if it fits the destination type T
     pub fn to_scaled_val<T>(&self) -> Result<T, DecimalError>
     where
@@ -159,6 +247,286 @@ impl Decimal {
     pub fn try_floor_u128(&self) -> Result<u128, DecimalError> {
         self.try_floor()
     }
+
+    /// Integer part of the decimal
+    pub fn trunc(&self) -> Self {
+        Self(self.0 / Self::wad() * Self::wad())
+    }
+
+    /// Fractional part of the decimal
+    pub fn fract(&self) -> Self {
+        Self(self.0 % Self::wad())
+    }
+
+    /// Round to `decimals` fractional digits
+    pub fn try_round_dp(&self, decimals: u32, mode: RoundingMode) -> Result<Self, DecimalError> {
+        if decimals as usize >= SCALE {
+            return Ok(*self);
+        }
+        self.try_round_to_multiple(Self(U192::exp10(SCALE - decimals as usize)), mode)
+    }
+
+    /// Round to a multiple of `step`, e.g. a tick or lot size
+    pub fn try_round_to_multiple(
+        &self,
+        step: Self,
+        mode: RoundingMode,
+    ) -> Result<Self, DecimalError> {
+        if step.0.is_zero() {
+            return Err(DecimalError::InvalidInput);
+        }
+        let steps = self
+            .0
+            .checked_div_rounded(step.0, mode)
+            .ok_or(DecimalError::MathOverflow)?;
+        Ok(Self(
+            steps
+                .checked_mul(step.0)
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+
+    /// Divide with 18 digits of precision, rounding the last digit with `mode`
+    pub fn try_div_rounded(self, rhs: Self, mode: RoundingMode) -> Result<Self, DecimalError> {
+        Ok(Self(
+            self.0
+                .checked_mul(Self::wad())
+                .ok_or(DecimalError::MathOverflow)?
+                .checked_div_rounded(rhs.0, mode)
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+
+    /// Reciprocal `1 / self`, rounding the last digit with `mode`
+    pub fn try_recip(self, mode: RoundingMode) -> Result<Self, DecimalError> {
+        Self::one().try_div_rounded(self, mode)
+    }
+
+    /// Multiply by `numerator / denominator` in one widened step, rounding the last digit with `mode`
+    pub fn try_mul_ratio(self, ratio: &Ratio, mode: RoundingMode) -> Result<Self, DecimalError> {
+        let product = U256::from(self.0) * U256::from(ratio.numerator);
+        let quotient = product
+            .checked_div_rounded(U256::from(ratio.denominator), mode)
+            .ok_or(DecimalError::MathOverflow)?;
+        Ok(Self(U192::try_from(quotient)?))
+    }
+
+    /// Relative change from `from` to `to` as a direction and a fraction, e.g. (Up, 0.05) for +5%
+    pub fn try_pct_change(
+        from: Self,
+        to: Self,
+        mode: RoundingMode,
+    ) -> Result<(Direction, Self), DecimalError> {
+        let direction = if to >= from {
+            Direction::Up
+        } else {
+            Direction::Down
+        };
+        let magnitude = to.abs_diff(from).try_div_rounded(from, mode)?;
+        Ok((direction, magnitude))
+    }
+
+    /// Fraction `part / whole`, rounding the last digit with `mode`
+    pub fn try_ratio_of(part: Self, whole: Self, mode: RoundingMode) -> Result<Self, DecimalError> {
+        part.try_div_rounded(whole, mode)
+    }
+
+    /// Mean of the values, rounding the last digit with `mode`
+    pub fn try_average(values: &[Self], mode: RoundingMode) -> Result<Self, DecimalError> {
+        if values.is_empty() {
+            return Err(DecimalError::InvalidInput);
+        }
+        let sum: Self = values.iter().try_sum()?;
+        Ok(Self(
+            sum.0
+                .checked_div_rounded(U192::from(values.len()), mode)
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+
+    /// Mean of `(value, weight)` pairs, rounding the last digit with `mode`
+    ///
+    /// The products are summed unscaled in 256 bits and divided once, so only the final digit
+    /// is rounded
+    pub fn try_weighted_average(
+        values: &[(Self, Self)],
+        mode: RoundingMode,
+    ) -> Result<Self, DecimalError> {
+        if values.is_empty() {
+            return Err(DecimalError::InvalidInput);
+        }
+        let add = |a: U256, b: U256| a.checked_add(b).ok_or(DecimalError::MathOverflow);
+        let (weighted_sum, total_weight) = values.iter().try_fold(
+            (U256::zero(), U256::zero()),
+            |(sum, total), (value, weight)| -> Result<_, DecimalError> {
+                let weighted = U256::from(value.0)
+                    .checked_mul(U256::from(weight.0))
+                    .ok_or(DecimalError::MathOverflow)?;
+                Ok((add(sum, weighted)?, add(total, U256::from(weight.0))?))
+            },
+        )?;
+        let average = weighted_sum
+            .checked_div_rounded(total_weight, mode)
+            .ok_or(DecimalError::MathOverflow)?;
+        Ok(Self(U192::try_from(average)?))
+    }
+
+    /// Divide into a whole quotient and a remainder, such that `self = q * rhs + r` exactly
+    pub fn try_div_rem(self, rhs: Self) -> Result<(Self, Self), DecimalError> {
+        let quotient = self
+            .0
+            .checked_div(rhs.0)
+            .ok_or(DecimalError::MathOverflow)?;
+        let remainder = self.try_rem(rhs)?;
+        Ok((
+            Self(
+                quotient
+                    .checked_mul(Self::wad())
+                    .ok_or(DecimalError::MathOverflow)?,
+            ),
+            remainder,
+        ))
+    }
+
+    /// Divide, return an error if the quotient can't be represented exactly with 18 digits
+    pub fn try_div_exact(self, rhs: Self) -> Result<Self, DecimalError> {
+        let scaled = self
+            .0
+            .checked_mul(Self::wad())
+            .ok_or(DecimalError::MathOverflow)?;
+        let remainder = scaled
+            .checked_rem(rhs.0)
+            .ok_or(DecimalError::MathOverflow)?;
+        if !remainder.is_zero() {
+            return Err(DecimalError::PrecisionLoss);
+        }
+        Ok(Self(scaled / rhs.0))
+    }
+
+    /// Square root, rounded down to 18 digits
+    pub fn sqrt(&self) -> Self {
+        // sqrt(raw / WAD) * WAD == sqrt(raw * WAD), widened so that it can't overflow
+        let root = (U256::from(self.0) * U256::from(WAD)).integer_sqrt();
+        Self(U192::try_from(root).unwrap_or(U192::MAX))
+    }
+
+    /// Check if the value is zero
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Check if the value is one
+    pub fn is_one(&self) -> bool {
+        self.0 == Self::wad()
+    }
+
+    /// Absolute difference between two values
+    pub fn abs_diff(self, other: Self) -> Self {
+        if self > other {
+            Self(self.0 - other.0)
+        } else {
+            Self(other.0 - self.0)
+        }
+    }
+
+    /// Restrict the value to `[min, max]`, return an error if `min > max`
+    pub fn try_clamp(self, min: Self, max: Self) -> Result<Self, DecimalError> {
+        if min > max {
+            return Err(DecimalError::InvalidInput);
+        }
+        Ok(self.max(min).min(max))
+    }
+
+    /// Check if two values differ by at most `tolerance`
+    pub fn approx_eq(self, other: Self, tolerance: Self) -> bool {
+        self.abs_diff(other) <= tolerance
+    }
+
+    /// Check if two values differ by at most `bps` relative to the larger one
+    ///
+    /// The check is symmetric: `a.approx_eq_bps(b, x) == b.approx_eq_bps(a, x)`
+    pub fn approx_eq_bps(self, other: Self, bps: u64) -> bool {
+        let diff = self.abs_diff(other).0;
+        let max = self.max(other).0;
+        // diff / max <= bps / 10_000, cross multiplied
+        match (
+            diff.checked_mul(U192::from(10_000u64)),
+            max.checked_mul(U192::from(bps)),
+        ) {
+            (Some(lhs), Some(rhs)) => lhs <= rhs,
+            (None, Some(_)) => false,
+            (Some(_), None) => true,
+            // Only reachable for huge values, where dividing loses no meaningful precision
+            (None, None) => diff / U192::from(bps) <= max / U192::from(10_000u64),
+        }
+    }
+
+    /// Check if two values differ by at most `ulps` units in the last place (10^-18)
+    pub fn approx_eq_ulps(self, other: Self, ulps: u64) -> bool {
+        self.abs_diff(other).0 <= U192::from(ulps)
+    }
+
+    /// Largest representable value
+    pub fn max_value() -> Self {
+        Self(U192::MAX)
+    }
+
+    /// Checked addition, return `None` on overflow
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.try_add(rhs).ok()
+    }
+
+    /// Checked subtraction, return `None` on underflow
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.try_sub(rhs).ok()
+    }
+
+    /// Checked multiplication, return `None` on overflow
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        self.try_mul(rhs).ok()
+    }
+
+    /// Checked division, return `None` on overflow or divide by zero
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.try_div(rhs).ok()
+    }
+
+    /// Saturating addition, clamp to the max value on overflow
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        self.checked_add(rhs).unwrap_or_else(Self::max_value)
+    }
+
+    /// Saturating subtraction, clamp to zero on underflow
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        self.checked_sub(rhs).unwrap_or_else(Self::zero)
+    }
+
+    /// Saturating multiplication, clamp to the max value on overflow
+    pub fn saturating_mul(self, rhs: Self) -> Self {
+        self.checked_mul(rhs).unwrap_or_else(Self::max_value)
+    }
+
+    /// Wrapping addition, return the result and whether it overflowed
+    pub fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+        let (val, overflow) = self.0.overflowing_add(rhs.0);
+        (Self(val), overflow)
+    }
+
+    /// Wrapping subtraction, return the result and whether it underflowed
+    pub fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+        let (val, overflow) = self.0.overflowing_sub(rhs.0);
+        (Self(val), overflow)
+    }
+
+    /// Wrapping multiplication, return the result and whether it overflowed
+    ///
+    /// On overflow the result is the wrapped scaled product divided by the WAD
+    pub fn overflowing_mul(self, rhs: Self) -> (Self, bool) {
+        match self.try_mul(rhs) {
+            Ok(val) => (val, false),
+            Err(_) => (Self(self.0.overflowing_mul(rhs.0).0 / Self::wad()), true),
+        }
+    }
 }
 
 impl fmt::Display for Decimal {
@@ -174,6 +542,39 @@ impl fmt::Display for Decimal {
     }
 }
 
+impl FromStr for Decimal {
+    type Err = DecimalError;
+
+    /// Parse a plain decimal string such as "12.5", up to 18 fractional digits
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (int_part, frac_part) = match s.find('.') {
+            Some(idx) => (&s[..idx], &s[idx + 1..]),
+            None => (s, "0"),
+        };
+        if int_part.is_empty() || frac_part.is_empty() {
+            return Err(DecimalError::ParseError);
+        }
+        if frac_part.len() > SCALE {
+            return Err(DecimalError::PrecisionLoss);
+        }
+        let parse = |digits: &str| {
+            if !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(DecimalError::ParseError);
+            }
+            U192::from_dec_str(digits).map_err(|_| DecimalError::MathOverflow)
+        };
+        let int_val = parse(int_part)?;
+        let frac_val = parse(frac_part)?;
+        let scaled_val = int_val
+            .checked_mul(Self::wad())
+            .ok_or(DecimalError::MathOverflow)?
+            .checked_add(frac_val * U192::exp10(SCALE - frac_part.len()))
+            .ok_or(DecimalError::MathOverflow)?;
+        Ok(Self(scaled_val))
+    }
+}
+
+#[cfg(not(feature = "strict"))]
 impl<T> From<T> for Decimal
 where
     T: Into<U128>,
@@ -187,11 +588,13 @@ where
 
 impl From<Rate> for Decimal {
     fn from(val: Rate) -> Self {
-        Self(val.to_scaled_val().unwrap())
+        Self(val.0.into())
     }
 }
 
 impl TryAdd for Decimal {
+    type Output = Self;
+
     fn try_add(self, rhs: Self) -> Result<Self, DecimalError> {
         Ok(Self(
             self.0
@@ -202,6 +605,8 @@ impl TryAdd for Decimal {
 }
 
 impl TrySub for Decimal {
+    type Output = Self;
+
     fn try_sub(self, rhs: Self) -> Result<Self, DecimalError> {
         Ok(Self(
             self.0
@@ -215,6 +620,8 @@ impl<T> TryDiv<T> for Decimal
 where
     T: Into<U192>,
 {
+    type Output = Self;
+
     fn try_div(self, rhs: T) -> Result<Self, DecimalError> {
         Ok(Self(
             self.0
@@ -225,12 +632,16 @@ where
 }
 
 impl TryDiv<Rate> for Decimal {
+    type Output = Self;
+
     fn try_div(self, rhs: Rate) -> Result<Self, DecimalError> {
         self.try_div(Self::from(rhs))
     }
 }
 
 impl TryDiv<Decimal> for Decimal {
+    type Output = Self;
+
     fn try_div(self, rhs: Self) -> Result<Self, DecimalError> {
         Ok(Self(
             self.0
@@ -246,6 +657,8 @@ impl<T> TryMul<T> for Decimal
 where
     T: Into<U192>,
 {
+    type Output = Self;
+
     fn try_mul(self, rhs: T) -> Result<Self, DecimalError> {
         Ok(Self(
             self.0
@@ -256,12 +669,16 @@ where
 }
 
 impl TryMul<Rate> for Decimal {
+    type Output = Self;
+
     fn try_mul(self, rhs: Rate) -> Result<Self, DecimalError> {
         self.try_mul(Self::from(rhs))
     }
 }
 
 impl TryMul<Decimal> for Decimal {
+    type Output = Self;
+
     fn try_mul(self, rhs: Self) -> Result<Self, DecimalError> {
         Ok(Self(
             self.0
@@ -273,6 +690,78 @@ impl TryMul<Decimal> for Decimal {
     }
 }
 
+impl TryAdd<Rate> for Decimal {
+    type Output = Self;
+
+    fn try_add(self, rhs: Rate) -> Result<Self, DecimalError> {
+        self.try_add(Self::from(rhs))
+    }
+}
+
+impl TrySub<Rate> for Decimal {
+    type Output = Self;
+
+    fn try_sub(self, rhs: Rate) -> Result<Self, DecimalError> {
+        self.try_sub(Self::from(rhs))
+    }
+}
+
+impl TryRem<Rate> for Decimal {
+    type Output = Self;
+
+    fn try_rem(self, rhs: Rate) -> Result<Self, DecimalError> {
+        self.try_rem(Self::from(rhs))
+    }
+}
+
+impl TryRem<Decimal> for Decimal {
+    type Output = Self;
+
+    fn try_rem(self, rhs: Self) -> Result<Self, DecimalError> {
+        Ok(Self(
+            self.0
+                .checked_rem(rhs.0)
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+}
+
+impl TrySum for Decimal {
+    fn try_sum<I: Iterator<Item = Self>>(mut iter: I) -> Result<Self, DecimalError> {
+        iter.try_fold(Self::zero(), |acc, x| acc.try_add(x))
+    }
+}
+
+impl<'a> TrySum<&'a Decimal> for Decimal {
+    fn try_sum<I: Iterator<Item = &'a Self>>(iter: I) -> Result<Self, DecimalError> {
+        Self::try_sum(iter.copied())
+    }
+}
+
+impl TryProduct for Decimal {
+    fn try_product<I: Iterator<Item = Self>>(mut iter: I) -> Result<Self, DecimalError> {
+        iter.try_fold(Self::one(), |acc, x| acc.try_mul(x))
+    }
+}
+
+impl<'a> TryProduct<&'a Decimal> for Decimal {
+    fn try_product<I: Iterator<Item = &'a Self>>(iter: I) -> Result<Self, DecimalError> {
+        Self::try_product(iter.copied())
+    }
+}
+
+impl PartialEq<Rate> for Decimal {
+    fn eq(&self, other: &Rate) -> bool {
+        self.0 == U192::from(other.0)
+    }
+}
+
+impl PartialOrd<Rate> for Decimal {
+    fn partial_cmp(&self, other: &Rate) -> Option<Ordering> {
+        self.0.partial_cmp(&U192::from(other.0))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -283,6 +772,7 @@ mod test {
     }
 
     #[test]
+    #[cfg(not(feature = "strict"))]
     fn test_decimal_from_to_percent() {
         let pct = 10; // 10%
         let x = Decimal::from_percent(pct);
@@ -290,4 +780,501 @@ mod test {
 
         assert_eq!(pct as u128, pct_actual);
     }
+
+    #[test]
+    fn test_decimal_to_percent_bps_decimal() {
+        let x = Decimal::try_from_bps(525u64).unwrap();
+        assert_eq!(x.to_percent::<u64>().unwrap(), 5);
+        assert_eq!(
+            x.to_percent_decimal().unwrap().to_string(),
+            "5.250000000000000000"
+        );
+        assert_eq!(
+            x.to_bps_decimal().unwrap(),
+            Decimal::try_from_int(525u64).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decimal_try_from_percent_bps() {
+        assert_eq!(Decimal::try_from_percent(10u64), "0.1".parse::<Decimal>());
+        assert_eq!(Decimal::try_from_bps(25u64), "0.0025".parse::<Decimal>());
+        assert_eq!(
+            Decimal::try_from_percent(U192::MAX),
+            Err(DecimalError::MathOverflow)
+        );
+        assert_eq!(
+            Decimal::try_from_bps(U192::MAX),
+            Err(DecimalError::MathOverflow)
+        );
+
+        let pct = Decimal::try_from_percent_decimal("12.5".parse().unwrap()).unwrap();
+        assert_eq!(pct.to_string(), "0.125000000000000000");
+        let bps = Decimal::try_from_bps_decimal("2.5".parse().unwrap()).unwrap();
+        assert_eq!(bps, Decimal::from_scaled_val(BPS_SCALER * 5 / 2));
+        assert_eq!(
+            Decimal::try_from_bps_decimal(Decimal::from_scaled_val(1u64)),
+            Err(DecimalError::PrecisionLoss)
+        );
+    }
+
+    #[test]
+    fn test_decimal_try_from_int() {
+        assert_eq!(Decimal::try_from_int(7u8), "7".parse::<Decimal>());
+        assert_eq!(Decimal::try_from_int(7u128), "7".parse::<Decimal>());
+        assert_eq!(Decimal::try_from_int(U128::from(7)), "7".parse::<Decimal>());
+        assert_eq!(
+            Decimal::try_from_int(u128::MAX).unwrap().to_string(),
+            "340282366920938463463374607431768211455.000000000000000000"
+        );
+        assert_eq!(
+            Decimal::try_from_int(U192::MAX),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_decimal_from_rate() {
+        let rate = Rate::from_scaled_val(u128::MAX);
+        assert_eq!(Decimal::from(rate), Decimal::from_scaled_val(u128::MAX));
+    }
+
+    #[test]
+    fn test_decimal_from_str() {
+        assert_eq!(
+            "5".parse::<Decimal>(),
+            Ok(Decimal::try_from_int(5u64).unwrap())
+        );
+        assert_eq!(
+            "0.000000000000000001".parse::<Decimal>(),
+            Ok(Decimal::from_scaled_val(1u64))
+        );
+        assert_eq!(
+            "123.456".parse::<Decimal>().unwrap().to_string(),
+            "123.456000000000000000"
+        );
+        assert_eq!(
+            "0.0000000000000000001".parse::<Decimal>(),
+            Err(DecimalError::PrecisionLoss)
+        );
+        for s in &["", ".5", "5.", "-1", "1.2.3", "1e5", " 1"] {
+            assert_eq!(s.parse::<Decimal>(), Err(DecimalError::ParseError));
+        }
+    }
+
+    #[test]
+    fn test_decimal_rate_cross_type() {
+        let ratio = Decimal::try_from_percent(80u64).unwrap();
+        let threshold = Rate::try_from_percent(75u64).unwrap();
+        assert!(ratio > threshold);
+        assert!(threshold < ratio);
+        assert_eq!(Decimal::try_from_percent(75u64).unwrap(), threshold);
+        assert_eq!(threshold, Decimal::try_from_percent(75u64).unwrap());
+        assert!(Decimal::from_scaled_val(U192::MAX) > Rate::from_scaled_val(U128::MAX));
+
+        assert_eq!(
+            ratio.try_sub(threshold),
+            Ok(Decimal::try_from_percent(5u64).unwrap())
+        );
+        assert_eq!(
+            threshold.try_add(ratio),
+            Ok(Decimal::try_from_percent(155u64).unwrap())
+        );
+        assert_eq!(threshold.try_sub(ratio), Err(DecimalError::MathOverflow));
+        assert_eq!(
+            threshold.try_mul(Decimal::try_from_int(2u64).unwrap()),
+            Ok(Decimal::try_from_percent(150u64).unwrap())
+        );
+        assert_eq!(
+            threshold.try_div(Decimal::try_from_int(3u64).unwrap()),
+            Ok(Decimal::try_from_percent(25u64).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_decimal_checked_saturating_overflowing() {
+        let one = Decimal::one();
+        let two = Decimal::try_from_int(2u64).unwrap();
+        let max = Decimal::max_value();
+        assert_eq!(
+            one.checked_add(two),
+            Some(Decimal::try_from_int(3u64).unwrap())
+        );
+        assert_eq!(max.checked_add(one), None);
+        assert_eq!(one.checked_sub(two), None);
+        assert_eq!(max.checked_mul(two), None);
+        assert_eq!(one.checked_div(Decimal::zero()), None);
+        assert_eq!(two.checked_div(two), Some(one));
+
+        assert_eq!(one.saturating_sub(two), Decimal::zero());
+        assert_eq!(two.saturating_sub(one), one);
+        assert_eq!(max.saturating_add(one), max);
+        assert_eq!(max.saturating_mul(two), max);
+
+        assert_eq!(
+            one.overflowing_add(two),
+            (Decimal::try_from_int(3u64).unwrap(), false)
+        );
+        assert_eq!(
+            max.overflowing_add(Decimal::from_scaled_val(2u64)),
+            (Decimal::from_scaled_val(1u64), true)
+        );
+        assert_eq!(
+            Decimal::zero().overflowing_sub(Decimal::from_scaled_val(1u64)),
+            (max, true)
+        );
+        assert_eq!(
+            two.overflowing_mul(two),
+            (Decimal::try_from_int(4u64).unwrap(), false)
+        );
+        assert!(max.overflowing_mul(two).1);
+    }
+
+    #[test]
+    fn test_decimal_comparison_utils() {
+        let a = Decimal::try_from_int(3u64).unwrap();
+        let b = Decimal::try_from_int(5u64).unwrap();
+        assert!(Decimal::zero().is_zero());
+        assert!(Decimal::one().is_one());
+        assert!(!a.is_zero() && !a.is_one());
+        assert_eq!(a.abs_diff(b), Decimal::try_from_int(2u64).unwrap());
+        assert_eq!(b.abs_diff(a), Decimal::try_from_int(2u64).unwrap());
+
+        assert_eq!(a.try_clamp(Decimal::one(), b), Ok(a));
+        assert_eq!(a.try_clamp(b, b), Ok(b));
+        assert_eq!(b.try_clamp(Decimal::one(), a), Ok(a));
+        assert_eq!(a.try_clamp(b, a), Err(DecimalError::InvalidInput));
+    }
+
+    #[test]
+    fn test_decimal_approx_eq() {
+        let price = Decimal::try_from_int(100u64).unwrap();
+        let feed = "100.5".parse::<Decimal>().unwrap();
+        assert!(price.approx_eq(feed, "0.5".parse().unwrap()));
+        assert!(!price.approx_eq(feed, "0.49".parse().unwrap()));
+
+        // 0.5 / 100.5 is ~49.75 bps
+        assert!(price.approx_eq_bps(feed, 50));
+        assert!(feed.approx_eq_bps(price, 50));
+        assert!(!price.approx_eq_bps(feed, 49));
+        assert!(!feed.approx_eq_bps(price, 49));
+        assert!(Decimal::zero().approx_eq_bps(Decimal::zero(), 0));
+
+        let max = Decimal::max_value();
+        assert!(max.approx_eq_bps(max.try_sub(Decimal::one()).unwrap(), 1));
+        assert!(!max.approx_eq_bps(Decimal::zero(), 9_999));
+        assert!(max.approx_eq_bps(Decimal::zero(), 10_000));
+
+        let x = Decimal::from_scaled_val(10u64);
+        assert!(x.approx_eq_ulps(Decimal::from_scaled_val(12u64), 2));
+        assert!(!x.approx_eq_ulps(Decimal::from_scaled_val(7u64), 2));
+    }
+
+    #[test]
+    fn test_decimal_trunc_fract() {
+        let x = "12.345".parse::<Decimal>().unwrap();
+        assert_eq!(x.trunc(), Decimal::try_from_int(12u64).unwrap());
+        assert_eq!(x.fract(), "0.345".parse::<Decimal>().unwrap());
+        assert_eq!(x.trunc().try_add(x.fract()), Ok(x));
+    }
+
+    #[test]
+    fn test_decimal_round_dp() {
+        let x = "2.345".parse::<Decimal>().unwrap();
+        let round = |mode| x.try_round_dp(2, mode).unwrap().to_string();
+        assert_eq!(round(RoundingMode::Down), "2.340000000000000000");
+        assert_eq!(round(RoundingMode::Up), "2.350000000000000000");
+        assert_eq!(round(RoundingMode::HalfUp), "2.350000000000000000");
+        assert_eq!(round(RoundingMode::HalfEven), "2.340000000000000000");
+        let y = "2.355".parse::<Decimal>().unwrap();
+        assert_eq!(
+            y.try_round_dp(2, RoundingMode::HalfEven)
+                .unwrap()
+                .to_string(),
+            "2.360000000000000000"
+        );
+        assert_eq!(
+            x.try_round_dp(0, RoundingMode::HalfUp),
+            Ok(Decimal::try_from_int(2u64).unwrap())
+        );
+        assert_eq!(x.try_round_dp(18, RoundingMode::Up), Ok(x));
+        assert_eq!(
+            Decimal::max_value().try_round_dp(0, RoundingMode::Up),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_decimal_round_to_multiple() {
+        let tick = "0.05".parse::<Decimal>().unwrap();
+        let price = "101.37".parse::<Decimal>().unwrap();
+        assert_eq!(
+            price.try_round_to_multiple(tick, RoundingMode::Down),
+            "101.35".parse()
+        );
+        assert_eq!(
+            price.try_round_to_multiple(tick, RoundingMode::HalfUp),
+            "101.35".parse()
+        );
+        assert_eq!(
+            price.try_round_to_multiple(tick, RoundingMode::Up),
+            "101.40".parse()
+        );
+        assert_eq!(
+            price.try_round_to_multiple(Decimal::zero(), RoundingMode::Up),
+            Err(DecimalError::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn test_decimal_div_rem() {
+        let a = "7.5".parse::<Decimal>().unwrap();
+        let b = Decimal::try_from_int(2u64).unwrap();
+        assert_eq!(a.try_rem(b), "1.5".parse());
+        let (q, r) = a.try_div_rem(b).unwrap();
+        assert_eq!(q, Decimal::try_from_int(3u64).unwrap());
+        assert_eq!(r, "1.5".parse::<Decimal>().unwrap());
+        assert_eq!(q.try_mul(b).unwrap().try_add(r), Ok(a));
+        assert_eq!(
+            a.try_div_rem(Decimal::zero()),
+            Err(DecimalError::MathOverflow)
+        );
+        assert_eq!(a.try_rem(Decimal::zero()), Err(DecimalError::MathOverflow));
+        assert_eq!(a.try_rem(Rate::half()), Ok(Decimal::zero()));
+        assert_eq!(a.try_rem(Rate::zero()), Err(DecimalError::MathOverflow));
+    }
+
+    #[test]
+    fn test_decimal_div_exact() {
+        let one = Decimal::one();
+        assert_eq!(
+            one.try_div_exact(Decimal::try_from_int(4u64).unwrap()),
+            "0.25".parse()
+        );
+        assert_eq!(
+            one.try_div_exact(Decimal::try_from_int(3u64).unwrap()),
+            Err(DecimalError::PrecisionLoss)
+        );
+        assert_eq!(
+            one.try_div_exact(Decimal::zero()),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_decimal_recip() {
+        let three = Decimal::try_from_int(3u64).unwrap();
+        assert_eq!(
+            three.try_recip(RoundingMode::Down).unwrap().to_string(),
+            "0.333333333333333333"
+        );
+        assert_eq!(
+            three.try_recip(RoundingMode::Up).unwrap().to_string(),
+            "0.333333333333333334"
+        );
+        assert_eq!(
+            "0.25"
+                .parse::<Decimal>()
+                .unwrap()
+                .try_recip(RoundingMode::Down),
+            Ok(Decimal::try_from_int(4u64).unwrap())
+        );
+        assert_eq!(
+            Decimal::zero().try_recip(RoundingMode::Down),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_decimal_pct_change_ratio_of() {
+        let from = Decimal::try_from_int(200u64).unwrap();
+        let up = Decimal::try_pct_change(
+            from,
+            Decimal::try_from_int(210u64).unwrap(),
+            RoundingMode::HalfUp,
+        );
+        assert_eq!(
+            up,
+            Ok((Direction::Up, Decimal::try_from_percent(5u64).unwrap()))
+        );
+        let down = Decimal::try_pct_change(
+            from,
+            Decimal::try_from_int(150u64).unwrap(),
+            RoundingMode::HalfUp,
+        );
+        assert_eq!(
+            down,
+            Ok((Direction::Down, Decimal::try_from_percent(25u64).unwrap()))
+        );
+        assert_eq!(
+            Decimal::try_pct_change(from, from, RoundingMode::HalfUp),
+            Ok((Direction::Up, Decimal::zero()))
+        );
+        assert_eq!(
+            Decimal::try_pct_change(Decimal::zero(), from, RoundingMode::HalfUp),
+            Err(DecimalError::MathOverflow)
+        );
+
+        let ratio = Decimal::try_ratio_of(
+            Decimal::try_from_int(2u64).unwrap(),
+            Decimal::try_from_int(3u64).unwrap(),
+            RoundingMode::HalfUp,
+        );
+        assert_eq!(ratio.unwrap().to_string(), "0.666666666666666667");
+    }
+
+    #[test]
+    fn test_decimal_try_sum_product() {
+        let values: Vec<Decimal> = (1..=4u64)
+            .map(|n| Decimal::try_from_int(n).unwrap())
+            .collect();
+        let sum: Decimal = values.iter().try_sum().unwrap();
+        assert_eq!(sum, Decimal::try_from_int(10u64).unwrap());
+        let product: Decimal = values.into_iter().try_product().unwrap();
+        assert_eq!(product, Decimal::try_from_int(24u64).unwrap());
+
+        let empty: [Decimal; 0] = [];
+        assert_eq!(empty.iter().try_sum(), Ok(Decimal::zero()));
+        assert_eq!(empty.iter().try_product(), Ok(Decimal::one()));
+
+        let overflow = [Decimal::max_value(), Decimal::one()];
+        assert_eq!(
+            overflow.iter().try_sum::<Decimal>(),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_decimal_average() {
+        let values = [
+            Decimal::one(),
+            Decimal::one(),
+            Decimal::try_from_int(2u64).unwrap(),
+        ];
+        assert_eq!(
+            Decimal::try_average(&values, RoundingMode::Down)
+                .unwrap()
+                .to_string(),
+            "1.333333333333333333"
+        );
+        assert_eq!(
+            Decimal::try_average(&values, RoundingMode::Up)
+                .unwrap()
+                .to_string(),
+            "1.333333333333333334"
+        );
+        assert_eq!(
+            Decimal::try_average(&[], RoundingMode::Down),
+            Err(DecimalError::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn test_decimal_weighted_average() {
+        // Average entry price after growing a 10 @ 100 position by 5 @ 130
+        let fills = [
+            (
+                Decimal::try_from_int(100u64).unwrap(),
+                Decimal::try_from_int(10u64).unwrap(),
+            ),
+            (
+                Decimal::try_from_int(130u64).unwrap(),
+                Decimal::try_from_int(5u64).unwrap(),
+            ),
+        ];
+        assert_eq!(
+            Decimal::try_weighted_average(&fills, RoundingMode::HalfUp),
+            Ok(Decimal::try_from_int(110u64).unwrap())
+        );
+
+        // Lamport-scale positions, whose products overflow 192 bits at WAD^2 scale
+        let positions = [
+            (
+                Decimal::try_from_int(10_000_000u64).unwrap(),
+                Decimal::try_from_int(1_000_000_000_000_000u64).unwrap(),
+            ),
+            (
+                Decimal::try_from_int(20_000_000u64).unwrap(),
+                Decimal::try_from_int(3_000_000_000_000_000u64).unwrap(),
+            ),
+            (
+                Decimal::try_from_int(1_000_000_000u64).unwrap(),
+                Decimal::try_from_int(u64::MAX).unwrap(),
+            ),
+        ];
+        assert_eq!(
+            Decimal::try_weighted_average(&positions[..2], RoundingMode::HalfUp),
+            Ok(Decimal::try_from_int(17_500_000u64).unwrap())
+        );
+        assert_eq!(
+            Decimal::try_weighted_average(&positions, RoundingMode::HalfUp),
+            "999787000.460019395455607190".parse::<Decimal>()
+        );
+
+        // Weights below the last digit are not lost to intermediate rounding
+        let tiny = Decimal::from_scaled_val(1u64);
+        let dust = [
+            (Decimal::try_from_int(3u64).unwrap(), tiny),
+            (Decimal::one(), tiny),
+        ];
+        assert_eq!(
+            Decimal::try_weighted_average(&dust, RoundingMode::Down),
+            Ok(Decimal::try_from_int(2u64).unwrap())
+        );
+
+        let zero_weight = [(Decimal::one(), Decimal::zero())];
+        assert_eq!(
+            Decimal::try_weighted_average(&zero_weight, RoundingMode::Down),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_decimal_sqrt() {
+        assert_eq!(
+            Decimal::try_from_int(2u64).unwrap().sqrt().to_string(),
+            "1.414213562373095048"
+        );
+        assert_eq!(
+            Decimal::try_from_int(16u64).unwrap().sqrt(),
+            Decimal::try_from_int(4u64).unwrap()
+        );
+        assert_eq!(
+            "0.25".parse::<Decimal>().unwrap().sqrt(),
+            "0.5".parse::<Decimal>().unwrap()
+        );
+        assert_eq!(Decimal::zero().sqrt(), Decimal::zero());
+        assert!(!Decimal::max_value().sqrt().is_zero());
+    }
+
+    #[test]
+    fn test_try_mul_ratio() {
+        let third = Ratio::new(1, 3);
+        let val = Decimal::from_scaled_val(1_000_000_000_000_000_000u128);
+        assert_eq!(
+            val.try_mul_ratio(&third, RoundingMode::Down)
+                .unwrap()
+                .to_string(),
+            "0.333333333333333333"
+        );
+        assert_eq!(
+            val.try_mul_ratio(&third, RoundingMode::Up)
+                .unwrap()
+                .to_string(),
+            "0.333333333333333334"
+        );
+        let val = Decimal::from_scaled_val(10u128);
+        assert_eq!(
+            val.try_mul_ratio(&Ratio::new(3, 3), RoundingMode::Down),
+            Ok(val)
+        );
+        assert_eq!(
+            Decimal::max_value().try_mul_ratio(&Ratio::new(3, 2), RoundingMode::Down),
+            Err(DecimalError::MathOverflow)
+        );
+        assert_eq!(
+            val.try_mul_ratio(&Ratio::new(1, 0), RoundingMode::Down),
+            Err(DecimalError::MathOverflow)
+        );
+    }
 }
@@ -0,0 +1,129 @@
+use std::fmt;
+
+use crate::common::uint::U192;
+
+/// Number of fractional digits of a scaled value when expressed in percent
+const PERCENT_SCALE: usize = 16;
+
+/// Number of fractional digits of a scaled value when expressed in bps
+const BPS_SCALE: usize = 14;
+
+/// Display adapter rendering a scaled value as a percentage, e.g. "5.25%"
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayPercent {
+    scaled_val: U192,
+    decimals: usize,
+}
+
+impl DisplayPercent {
+    pub(crate) fn new(scaled_val: U192, decimals: usize) -> Self {
+        Self {
+            scaled_val,
+            decimals,
+        }
+    }
+}
+
+impl fmt::Display for DisplayPercent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shown = self.decimals.min(PERCENT_SCALE);
+        let divisor = U192::exp10(PERCENT_SCALE - shown);
+        // Round half up on the last displayed digit
+        let mut val = self.scaled_val / divisor;
+        if self.scaled_val % divisor >= (divisor + 1) / 2 {
+            val = val.saturating_add(U192::one());
+        }
+        let mut s = fmt_fixed(val, shown);
+        if self.decimals > shown {
+            s.push_str(&"0".repeat(self.decimals - shown));
+        }
+        s.push('%');
+        f.write_str(&s)
+    }
+}
+
+/// Display adapter rendering a scaled value in basis points, e.g. "525 bps"
+///
+/// Fractional bps are kept, trailing zeros are trimmed: "2.5 bps"
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayBps {
+    scaled_val: U192,
+}
+
+impl DisplayBps {
+    pub(crate) fn new(scaled_val: U192) -> Self {
+        Self { scaled_val }
+    }
+}
+
+impl fmt::Display for DisplayBps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = fmt_fixed(self.scaled_val, BPS_SCALE);
+        if s.contains('.') {
+            let trimmed = s.trim_end_matches('0').trim_end_matches('.').len();
+            s.truncate(trimmed);
+        }
+        s.push_str(" bps");
+        f.write_str(&s)
+    }
+}
+
+/// Render an integer as a fixed point number with `decimals` fractional digits
+fn fmt_fixed(val: U192, decimals: usize) -> String {
+    let mut s = val.to_string();
+    if decimals == 0 {
+        return s;
+    }
+    if s.len() <= decimals {
+        s.insert_str(0, &"0".repeat(decimals - s.len()));
+        s.insert_str(0, "0.");
+    } else {
+        s.insert(s.len() - decimals, '.');
+    }
+    s
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::BPS_SCALER;
+    use crate::decimal::Decimal;
+    use crate::rate::Rate;
+
+    #[test]
+    fn test_display_percent() {
+        let rate = Rate::try_from_bps(525u64).unwrap();
+        assert_eq!(rate.display_percent(2).to_string(), "5.25%");
+        assert_eq!(rate.display_percent(1).to_string(), "5.3%");
+        assert_eq!(rate.display_percent(0).to_string(), "5%");
+        assert_eq!(
+            rate.display_percent(20).to_string(),
+            "5.25000000000000000000%"
+        );
+        assert_eq!(
+            Decimal::try_from_int(2u64)
+                .unwrap()
+                .display_percent(1)
+                .to_string(),
+            "200.0%"
+        );
+        assert_eq!(Decimal::zero().display_percent(2).to_string(), "0.00%");
+    }
+
+    #[test]
+    fn test_display_bps() {
+        assert_eq!(
+            Rate::try_from_bps(525u64)
+                .unwrap()
+                .display_bps()
+                .to_string(),
+            "525 bps"
+        );
+        assert_eq!(
+            Decimal::from_scaled_val(BPS_SCALER * 5 / 2)
+                .display_bps()
+                .to_string(),
+            "2.5 bps"
+        );
+        assert_eq!(Decimal::zero().display_bps().to_string(), "0 bps");
+    }
+}
@@ -0,0 +1,193 @@
+use crate::common::uint::{U128, U192};
+use crate::common::*;
+use crate::decimal::*;
+use crate::error::*;
+use crate::rate::*;
+
+/// Exponential moving average of decimal samples
+///
+/// Each period the previous average keeps `1 - smoothing` of its weight and the new sample
+/// gets the rest. Samples spaced in time decay the average by `(1 - smoothing)^elapsed`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ema {
+    /// Current average, zero until the first sample
+    pub value: Decimal,
+    /// Weight of a new sample after one period, in `[0, 1]`
+    pub smoothing: Rate,
+    /// Timestamp of the last timed sample
+    pub last_update: u64,
+    /// Whether a sample was recorded yet
+    pub initialized: bool,
+}
+
+impl Ema {
+    /// Packed size in bytes
+    pub const LEN: usize = 24 + 16 + 8 + 1;
+
+    /// Create an average with the given per period smoothing factor
+    pub fn try_new(smoothing: Rate) -> Result<Self, DecimalError> {
+        if smoothing > Rate::one() {
+            return Err(DecimalError::InvalidInput);
+        }
+        Ok(Self {
+            smoothing,
+            ..Self::default()
+        })
+    }
+
+    /// Create an average where a sample loses half of its weight after `half_life` periods
+    pub fn try_with_half_life(half_life: u64) -> Result<Self, DecimalError> {
+        if half_life == 0 {
+            return Err(DecimalError::InvalidInput);
+        }
+        // Bisect the retained weight per period so that retain^half_life = 1/2
+        let mut low = Rate::half();
+        let mut high = Rate::one();
+        while high.try_sub(low)? > Rate::from_scaled_val(1u64) {
+            let mid = Rate(low.0 + (high.0 - low.0) / 2);
+            if mid.try_pow(half_life)? > Rate::half() {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        Self::try_new(Rate::one().try_sub(low)?)
+    }
+
+    /// Record a sample one period after the previous one, return the new average
+    pub fn try_update(&mut self, sample: Decimal) -> Result<Decimal, DecimalError> {
+        self.try_apply(sample, 1)
+    }
+
+    /// Record a sample taken at `timestamp`, decaying the average by the elapsed periods
+    pub fn try_update_at(
+        &mut self,
+        sample: Decimal,
+        timestamp: u64,
+    ) -> Result<Decimal, DecimalError> {
+        let elapsed = if self.initialized {
+            timestamp
+                .checked_sub(self.last_update)
+                .ok_or(DecimalError::InvalidInput)?
+        } else {
+            0
+        };
+        let value = self.try_apply(sample, elapsed)?;
+        self.last_update = timestamp;
+        Ok(value)
+    }
+
+    fn try_apply(&mut self, sample: Decimal, periods: u64) -> Result<Decimal, DecimalError> {
+        if !self.initialized {
+            self.value = sample;
+            self.initialized = true;
+            return Ok(sample);
+        }
+        let retained = Rate::one().try_sub(self.smoothing)?.try_pow(periods)?;
+        self.value = self
+            .value
+            .try_mul(retained)?
+            .try_add(sample.try_mul(Rate::one().try_sub(retained)?)?)?;
+        Ok(self.value)
+    }
+
+    /// Serialize into a slice of exactly `LEN` bytes
+    pub fn pack_into_slice(&self, dst: &mut [u8]) -> Result<(), DecimalError> {
+        if dst.len() != Self::LEN {
+            return Err(DecimalError::InvalidInput);
+        }
+        self.value.0.to_little_endian(&mut dst[0..24]);
+        self.smoothing.0.to_little_endian(&mut dst[24..40]);
+        dst[40..48].copy_from_slice(&self.last_update.to_le_bytes());
+        dst[48] = self.initialized as u8;
+        Ok(())
+    }
+
+    /// Deserialize from a slice of exactly `LEN` bytes
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, DecimalError> {
+        if src.len() != Self::LEN || src[48] > 1 {
+            return Err(DecimalError::InvalidInput);
+        }
+        let mut last_update = [0u8; 8];
+        last_update.copy_from_slice(&src[40..48]);
+        Ok(Self {
+            value: Decimal(U192::from_little_endian(&src[0..24])),
+            smoothing: Rate(U128::from_little_endian(&src[24..40])),
+            last_update: u64::from_le_bytes(last_update),
+            initialized: src[48] == 1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_update() {
+        let mut ema = Ema::try_new(Rate::try_from_percent(10u64).unwrap()).unwrap();
+        assert_eq!(
+            ema.try_update(Decimal::try_from_int(100u64).unwrap()),
+            Ok(Decimal::try_from_int(100u64).unwrap())
+        );
+        assert_eq!(
+            ema.try_update(Decimal::try_from_int(200u64).unwrap()),
+            Ok(Decimal::try_from_int(110u64).unwrap())
+        );
+        assert_eq!(
+            ema.try_update(Decimal::try_from_int(110u64).unwrap()),
+            Ok(Decimal::try_from_int(110u64).unwrap())
+        );
+        assert_eq!(
+            Ema::try_new(Rate::try_from_percent(101u64).unwrap()),
+            Err(DecimalError::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn test_update_at_decays_with_time() {
+        let mut ema = Ema::try_with_half_life(60).unwrap();
+        ema.try_update_at(Decimal::try_from_int(100u64).unwrap(), 1_000)
+            .unwrap();
+        assert_eq!(ema.last_update, 1_000);
+        // After one half-life the previous average and the new sample weigh the same
+        let value = ema
+            .try_update_at(Decimal::try_from_int(200u64).unwrap(), 1_060)
+            .unwrap();
+        assert!(value.approx_eq_bps(Decimal::try_from_int(150u64).unwrap(), 1));
+        assert_eq!(
+            ema.try_update_at(Decimal::try_from_int(1u64).unwrap(), 1_059),
+            Err(DecimalError::InvalidInput)
+        );
+        // No elapsed time keeps the average
+        assert_eq!(
+            ema.try_update_at(Decimal::try_from_int(1u64).unwrap(), 1_060),
+            Ok(value)
+        );
+    }
+
+    #[test]
+    fn test_half_life_smoothing() {
+        let ema = Ema::try_with_half_life(1).unwrap();
+        assert!(ema.smoothing.approx_eq_ulps(Rate::half(), 1));
+        assert_eq!(Ema::try_with_half_life(0), Err(DecimalError::InvalidInput));
+    }
+
+    #[test]
+    fn test_pack_unpack() {
+        let mut ema = Ema::try_with_half_life(3_600).unwrap();
+        ema.try_update_at(Decimal::try_from_int(42u64).unwrap(), 7)
+            .unwrap();
+        let mut buf = [0u8; Ema::LEN];
+        ema.pack_into_slice(&mut buf).unwrap();
+        assert_eq!(Ema::unpack_from_slice(&buf), Ok(ema));
+        assert_eq!(
+            Ema::unpack_from_slice(&buf[1..]),
+            Err(DecimalError::InvalidInput)
+        );
+        assert_eq!(
+            ema.pack_into_slice(&mut [0u8; Ema::LEN + 1]),
+            Err(DecimalError::InvalidInput)
+        );
+    }
+}
@@ -1,4 +1,11 @@
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum DecimalError {
     MathOverflow,
+    /// Value has more significant digits than can be represented
+    PrecisionLoss,
+    /// String is not a valid decimal number
+    ParseError,
+    /// Argument is outside of the accepted domain
+    InvalidInput,
 }
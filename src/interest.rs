@@ -0,0 +1,154 @@
+use std::convert::TryFrom;
+
+use crate::common::uint::{U192, U256};
+use crate::common::*;
+use crate::decimal::*;
+use crate::error::*;
+use crate::rate::*;
+
+/// Cumulative interest index, the growth of one unit of debt since the index was created
+///
+/// The period is whatever unit `elapsed` is counted in, slots or seconds, as long as the
+/// rate passed to `try_accrue` is quoted per that period.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InterestIndex {
+    /// Current index, starts at one
+    pub value: Decimal,
+}
+
+impl Default for InterestIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InterestIndex {
+    /// Packed size in bytes
+    pub const LEN: usize = 24;
+
+    /// Create an index starting at one
+    pub fn new() -> Self {
+        Self {
+            value: Decimal::one(),
+        }
+    }
+
+    /// Compound the index by `(1 + rate_per_period)^elapsed`, return the new index
+    pub fn try_accrue(
+        &mut self,
+        rate_per_period: Rate,
+        elapsed: u64,
+    ) -> Result<Decimal, DecimalError> {
+        if elapsed == 0 {
+            return Ok(self.value);
+        }
+        let growth = Rate::one().try_add(rate_per_period)?.try_pow(elapsed)?;
+        self.value = self.value.try_mul(growth)?;
+        Ok(self.value)
+    }
+
+    /// Debt borrowed at `snapshot` grown to the current index, rounded up
+    pub fn try_scale_debt(
+        &self,
+        borrowed: Decimal,
+        snapshot: &InterestIndex,
+    ) -> Result<Decimal, DecimalError> {
+        let debt = U256::from(borrowed.0)
+            .checked_mul(U256::from(self.value.0))
+            .ok_or(DecimalError::MathOverflow)?
+            .checked_div_rounded(U256::from(snapshot.value.0), RoundingMode::Up)
+            .ok_or(DecimalError::MathOverflow)?;
+        Ok(Decimal(U192::try_from(debt)?))
+    }
+
+    /// Serialize into a slice of exactly `LEN` bytes
+    pub fn pack_into_slice(&self, dst: &mut [u8]) -> Result<(), DecimalError> {
+        if dst.len() != Self::LEN {
+            return Err(DecimalError::InvalidInput);
+        }
+        self.value.0.to_little_endian(dst);
+        Ok(())
+    }
+
+    /// Deserialize from a slice of exactly `LEN` bytes
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, DecimalError> {
+        if src.len() != Self::LEN {
+            return Err(DecimalError::InvalidInput);
+        }
+        Ok(Self {
+            value: Decimal(U192::from_little_endian(src)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_accrue() {
+        let mut index = InterestIndex::new();
+        let rate: Rate = "0.1".parse().unwrap();
+        assert_eq!(index.try_accrue(rate, 0), Ok(Decimal::one()));
+        assert_eq!(index.try_accrue(rate, 2), "1.21".parse::<Decimal>());
+        assert_eq!(index.try_accrue(rate, 1), "1.331".parse::<Decimal>());
+
+        // accruing in steps matches accruing at once
+        let mut stepped = InterestIndex::new();
+        stepped.try_accrue(rate, 3).unwrap();
+        assert_eq!(stepped, index);
+    }
+
+    #[test]
+    fn test_scale_debt() {
+        let snapshot = InterestIndex {
+            value: "1.1".parse().unwrap(),
+        };
+        let current = InterestIndex {
+            value: "1.21".parse().unwrap(),
+        };
+        let borrowed: Decimal = "100".parse().unwrap();
+        assert_eq!(
+            current.try_scale_debt(borrowed, &snapshot),
+            "110".parse::<Decimal>()
+        );
+        let third = InterestIndex {
+            value: "3".parse().unwrap(),
+        };
+        assert_eq!(
+            InterestIndex::new()
+                .try_scale_debt(Decimal::one(), &third)
+                .unwrap()
+                .to_string(),
+            "0.333333333333333334"
+        );
+        let zero = InterestIndex {
+            value: Decimal::zero(),
+        };
+        assert_eq!(
+            current.try_scale_debt(borrowed, &zero),
+            Err(DecimalError::MathOverflow)
+        );
+        let huge = InterestIndex {
+            value: Decimal(U192::MAX / 2),
+        };
+        assert_eq!(
+            huge.try_scale_debt(Decimal(U192::MAX / 2), &current),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_pack_unpack() {
+        let index = InterestIndex {
+            value: "1.234567890123456789".parse().unwrap(),
+        };
+        let mut buf = [0u8; InterestIndex::LEN];
+        index.pack_into_slice(&mut buf).unwrap();
+        assert_eq!(InterestIndex::unpack_from_slice(&buf), Ok(index));
+        assert_eq!(
+            InterestIndex::unpack_from_slice(&buf[1..]),
+            Err(DecimalError::InvalidInput)
+        );
+    }
+}
@@ -1,7 +1,15 @@
+pub mod accumulator;
+pub mod allocation;
 pub mod common;
 pub mod decimal;
+pub mod display;
+pub mod ema;
 pub mod error;
-#[cfg(feature = "ops-traits")]
+pub mod interest;
+#[cfg(all(feature = "ops-traits", not(feature = "strict")))]
 pub mod ops_traits;
 pub mod rate;
 pub mod ratio;
+pub mod stats;
+pub mod twap;
+pub mod vault;
@@ -1,125 +1,232 @@
-use std::ops::{Add, Div, Mul, Sub};
+//! Operator overloads for [`Decimal`] and [`Rate`], panicking where the `Try*` traits would error
+//!
+//! Output types follow one rule: operations between two values of the same type return that type,
+//! and any operation mixing a `Decimal` with a `Rate` returns a `Decimal`. Integers are plain
+//! multipliers and divisors (`Decimal * 3`, `3 * Decimal`, `Rate / 2`) and keep the decimal type.
+//! Every operator is also implemented on references, and the assign variants are implemented for
+//! every right hand side whose operator keeps the left hand side type.
+
+use std::fmt::Display;
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Rem, RemAssign, Sub, SubAssign};
 
 use crate::{
     common::{
         uint::{U128, U192},
-        TryAdd, TryDiv, TryMul, TrySub,
+        TryAdd, TryDiv, TryMul, TryRem, TrySub,
     },
     decimal::Decimal,
+    error::DecimalError,
     rate::Rate,
 };
 
-impl<T> Mul<T> for Decimal
-where
-    T: Into<U192>,
-{
-    type Output = Decimal;
-    fn mul(self, rhs: T) -> Decimal {
-        self.try_mul(rhs).unwrap()
+/// Unwrap an operation result, panicking with both operands at the caller location
+#[track_caller]
+fn unwrap_op<T>(res: Result<T, DecimalError>, lhs: impl Display, op: &str, rhs: impl Display) -> T {
+    match res {
+        Ok(val) => val,
+        Err(err) => panic!("{:?} in `{} {} {}`", err, lhs, op, rhs),
     }
 }
 
-impl Mul<Decimal> for Decimal {
-    type Output = Decimal;
-
-    fn mul(self, rhs: Decimal) -> Decimal {
-        self.try_mul(rhs).unwrap()
-    }
+/// Implement a binary operator for values and references from a fallible expression
+macro_rules! impl_op {
+    ($Op:ident, $op:ident, $sym:literal, $Lhs:ty, $Rhs:ty => $Out:ty, |$a:ident, $b:ident| $res:expr) => {
+        impl $Op<$Rhs> for $Lhs {
+            type Output = $Out;
+
+            #[track_caller]
+            fn $op(self, rhs: $Rhs) -> $Out {
+                let ($a, $b) = (self, rhs);
+                unwrap_op($res, self, $sym, rhs)
+            }
+        }
+
+        impl<'a> $Op<&'a $Rhs> for $Lhs {
+            type Output = $Out;
+
+            #[track_caller]
+            fn $op(self, rhs: &'a $Rhs) -> $Out {
+                $Op::$op(self, *rhs)
+            }
+        }
+
+        impl<'a> $Op<$Rhs> for &'a $Lhs {
+            type Output = $Out;
+
+            #[track_caller]
+            fn $op(self, rhs: $Rhs) -> $Out {
+                $Op::$op(*self, rhs)
+            }
+        }
+
+        impl<'a, 'b> $Op<&'b $Rhs> for &'a $Lhs {
+            type Output = $Out;
+
+            #[track_caller]
+            fn $op(self, rhs: &'b $Rhs) -> $Out {
+                $Op::$op(*self, *rhs)
+            }
+        }
+    };
 }
 
-impl Mul<Rate> for Decimal {
-    type Output = Decimal;
-
-    fn mul(self, rhs: Rate) -> Decimal {
-        self.try_mul(Self::from(rhs)).unwrap()
-    }
+/// Implement an operator with an integer right hand side, which is used unscaled
+macro_rules! impl_int_op {
+    ($Op:ident, $op:ident, $try_op:ident, $sym:literal, $Lhs:ty, $Uint:ty) => {
+        impl<T> $Op<T> for $Lhs
+        where
+            T: Into<$Uint>,
+        {
+            type Output = $Lhs;
+
+            #[track_caller]
+            fn $op(self, rhs: T) -> $Lhs {
+                let rhs: $Uint = rhs.into();
+                unwrap_op(self.$try_op(rhs), self, $sym, rhs)
+            }
+        }
+
+        impl<'a, T> $Op<T> for &'a $Lhs
+        where
+            T: Into<$Uint>,
+        {
+            type Output = $Lhs;
+
+            #[track_caller]
+            fn $op(self, rhs: T) -> $Lhs {
+                $Op::$op(*self, rhs)
+            }
+        }
+    };
 }
 
-impl<T> Div<T> for Decimal
-where
-    T: Into<U192>,
-{
-    type Output = Decimal;
-    fn div(self, rhs: T) -> Decimal {
-        self.try_div(rhs).unwrap()
-    }
+/// Implement `int * decimal` for the primitive integer types
+macro_rules! impl_int_lhs_mul {
+    ($Rhs:ty => $($int:ty),*) => {
+        $(
+            impl Mul<$Rhs> for $int {
+                type Output = $Rhs;
+
+                #[track_caller]
+                fn mul(self, rhs: $Rhs) -> $Rhs {
+                    rhs * self
+                }
+            }
+
+            impl<'a> Mul<&'a $Rhs> for $int {
+                type Output = $Rhs;
+
+                #[track_caller]
+                fn mul(self, rhs: &'a $Rhs) -> $Rhs {
+                    *rhs * self
+                }
+            }
+        )*
+    };
 }
 
-impl Div<Decimal> for Decimal {
-    type Output = Decimal;
-
-    fn div(self, rhs: Decimal) -> Decimal {
-        self.try_div(rhs).unwrap()
-    }
+/// Implement `Sum` and `Product` for values and references
+macro_rules! impl_iter_ops {
+    ($T:ty) => {
+        impl Sum for $T {
+            #[track_caller]
+            fn sum<I: Iterator<Item = $T>>(iter: I) -> $T {
+                let mut acc = <$T>::zero();
+                for x in iter {
+                    acc = unwrap_op(acc.try_add(x), acc, "+", x);
+                }
+                acc
+            }
+        }
+
+        impl<'a> Sum<&'a $T> for $T {
+            #[track_caller]
+            fn sum<I: Iterator<Item = &'a $T>>(iter: I) -> $T {
+                <$T as Sum>::sum(iter.copied())
+            }
+        }
+
+        impl Product for $T {
+            #[track_caller]
+            fn product<I: Iterator<Item = $T>>(iter: I) -> $T {
+                let mut acc = <$T>::one();
+                for x in iter {
+                    acc = unwrap_op(acc.try_mul(x), acc, "*", x);
+                }
+                acc
+            }
+        }
+
+        impl<'a> Product<&'a $T> for $T {
+            #[track_caller]
+            fn product<I: Iterator<Item = &'a $T>>(iter: I) -> $T {
+                <$T as Product>::product(iter.copied())
+            }
+        }
+    };
 }
 
-impl Add<Decimal> for Decimal {
-    type Output = Decimal;
-
-    fn add(self, rhs: Decimal) -> Decimal {
-        self.try_add(rhs).unwrap()
-    }
+/// Implement an assign operator for every right hand side keeping the left hand side type
+macro_rules! impl_assign_op {
+    ($OpAssign:ident, $op_assign:ident, $Op:ident, $op:ident, $Lhs:ty) => {
+        impl<T> $OpAssign<T> for $Lhs
+        where
+            $Lhs: $Op<T, Output = $Lhs>,
+        {
+            #[track_caller]
+            fn $op_assign(&mut self, rhs: T) {
+                *self = $Op::$op(*self, rhs);
+            }
+        }
+    };
 }
 
-impl Sub<Decimal> for Decimal {
-    type Output = Decimal;
-
-    fn sub(self, rhs: Decimal) -> Decimal {
-        self.try_sub(rhs).unwrap()
-    }
-}
-
-impl<T> Mul<T> for Rate
-where
-    T: Into<U128>,
-{
-    type Output = Rate;
-    fn mul(self, rhs: T) -> Rate {
-        self.try_mul(rhs).unwrap()
-    }
-}
-
-impl Mul<Rate> for Rate {
-    type Output = Rate;
-
-    fn mul(self, rhs: Rate) -> Rate {
-        self.try_mul(rhs).unwrap()
-    }
-}
-
-impl<T> Div<T> for Rate
-where
-    T: Into<U128>,
-{
-    type Output = Rate;
-    fn div(self, rhs: T) -> Rate {
-        self.try_div(rhs).unwrap()
-    }
-}
-
-impl Div<Rate> for Rate {
-    type Output = Rate;
-
-    fn div(self, rhs: Rate) -> Rate {
-        self.try_div(rhs).unwrap()
-    }
-}
-
-impl Add<Rate> for Rate {
-    type Output = Rate;
-
-    fn add(self, rhs: Rate) -> Rate {
-        self.try_add(rhs).unwrap()
-    }
-}
-
-impl Sub<Rate> for Rate {
-    type Output = Rate;
-
-    fn sub(self, rhs: Rate) -> Rate {
-        self.try_sub(rhs).unwrap()
-    }
-}
+impl_op!(Add, add, "+", Decimal, Decimal => Decimal, |a, b| a.try_add(b));
+impl_op!(Sub, sub, "-", Decimal, Decimal => Decimal, |a, b| a.try_sub(b));
+impl_op!(Mul, mul, "*", Decimal, Decimal => Decimal, |a, b| a.try_mul(b));
+impl_op!(Div, div, "/", Decimal, Decimal => Decimal, |a, b| a.try_div(b));
+impl_op!(Rem, rem, "%", Decimal, Decimal => Decimal, |a, b| a.try_rem(b));
+
+impl_op!(Add, add, "+", Decimal, Rate => Decimal, |a, b| a.try_add(b));
+impl_op!(Sub, sub, "-", Decimal, Rate => Decimal, |a, b| a.try_sub(b));
+impl_op!(Mul, mul, "*", Decimal, Rate => Decimal, |a, b| a.try_mul(b));
+impl_op!(Div, div, "/", Decimal, Rate => Decimal, |a, b| a.try_div(b));
+impl_op!(Rem, rem, "%", Decimal, Rate => Decimal, |a, b| a.try_rem(b));
+impl_op!(Add, add, "+", Rate, Decimal => Decimal, |a, b| a.try_add(b));
+impl_op!(Sub, sub, "-", Rate, Decimal => Decimal, |a, b| a.try_sub(b));
+impl_op!(Mul, mul, "*", Rate, Decimal => Decimal, |a, b| a.try_mul(b));
+impl_op!(Div, div, "/", Rate, Decimal => Decimal, |a, b| a.try_div(b));
+impl_op!(Rem, rem, "%", Rate, Decimal => Decimal, |a, b| a.try_rem(b));
+
+impl_op!(Add, add, "+", Rate, Rate => Rate, |a, b| a.try_add(b));
+impl_op!(Sub, sub, "-", Rate, Rate => Rate, |a, b| a.try_sub(b));
+impl_op!(Mul, mul, "*", Rate, Rate => Rate, |a, b| a.try_mul(b));
+impl_op!(Div, div, "/", Rate, Rate => Rate, |a, b| a.try_div(b));
+impl_op!(Rem, rem, "%", Rate, Rate => Rate, |a, b| a.try_rem(b));
+
+impl_int_op!(Mul, mul, try_mul, "*", Decimal, U192);
+impl_int_op!(Div, div, try_div, "/", Decimal, U192);
+impl_int_op!(Mul, mul, try_mul, "*", Rate, U128);
+impl_int_op!(Div, div, try_div, "/", Rate, U128);
+
+impl_int_lhs_mul!(Decimal => u8, u16, u32, u64, u128, usize);
+impl_int_lhs_mul!(Rate => u8, u16, u32, u64, u128, usize);
+
+impl_assign_op!(AddAssign, add_assign, Add, add, Decimal);
+impl_assign_op!(SubAssign, sub_assign, Sub, sub, Decimal);
+impl_assign_op!(MulAssign, mul_assign, Mul, mul, Decimal);
+impl_assign_op!(DivAssign, div_assign, Div, div, Decimal);
+impl_assign_op!(RemAssign, rem_assign, Rem, rem, Decimal);
+
+impl_iter_ops!(Decimal);
+impl_iter_ops!(Rate);
+
+impl_assign_op!(AddAssign, add_assign, Add, add, Rate);
+impl_assign_op!(SubAssign, sub_assign, Sub, sub, Rate);
+impl_assign_op!(MulAssign, mul_assign, Mul, mul, Rate);
+impl_assign_op!(DivAssign, div_assign, Div, div, Rate);
+impl_assign_op!(RemAssign, rem_assign, Rem, rem, Rate);
 
 #[cfg(test)]
 mod tests {
@@ -212,4 +319,124 @@ mod tests {
         let c = a - b;
         assert_eq!(c.to_string(), "0.000000000000000001");
     }
+
+    #[test]
+    #[should_panic(expected = "MathOverflow in `1.000000000000000000 - 2.000000000000000000`")]
+    fn test_sub_decimal_underflow_message() {
+        let _ = Decimal::from(1) - Decimal::from(2);
+    }
+
+    #[test]
+    #[should_panic(expected = "MathOverflow in `1.000000000000000000 / 0`")]
+    fn test_div_int_by_zero_message() {
+        let _ = Rate::one() / 0u64;
+    }
+
+    #[test]
+    fn test_sum_product() {
+        let values = [Decimal::from(2), Decimal::from(3), Decimal::from(4)];
+        assert_eq!(values.iter().sum::<Decimal>(), Decimal::from(9));
+        assert_eq!(
+            values.iter().copied().product::<Decimal>(),
+            Decimal::from(24)
+        );
+        let rates = vec![Rate::half(), Rate::half()];
+        assert_eq!(rates.iter().product::<Rate>(), Rate::from_percent(25));
+        assert_eq!(rates.into_iter().sum::<Rate>(), Rate::one());
+    }
+
+    #[test]
+    #[should_panic(expected = " + 1.000000000000000000`")]
+    fn test_sum_overflow_message() {
+        let values = [Decimal(U192::MAX), Decimal::one()];
+        let _ = values.iter().sum::<Decimal>();
+    }
+
+    #[test]
+    fn test_rate_times_decimal() {
+        let a = Rate::from_percent(3);
+        let b = Decimal::from(4);
+        assert_eq!(a * b, b * a);
+        assert_eq!(
+            Rate::from_percent(50) / Decimal::from(2),
+            Decimal::from_percent(25)
+        );
+    }
+
+    #[test]
+    fn test_add_sub_decimal_rate() {
+        let a = Decimal::from(1);
+        let r = Rate::half();
+        assert_eq!(a + r, Decimal::from_percent(150));
+        assert_eq!(r + a, Decimal::from_percent(150));
+        assert_eq!(a - r, Decimal::from_percent(50));
+        let mut b = Decimal::from(2);
+        b -= Rate::one();
+        assert_eq!(b, Decimal::from(1));
+    }
+
+    #[test]
+    fn test_div_rate() {
+        let a = Decimal::from(3);
+        let b = Rate::from_percent(50);
+        assert_eq!(a / b, Decimal::from(6));
+    }
+
+    #[test]
+    fn test_rem() {
+        let a = Decimal::from_scaled_val(7_500_000_000_000_000_000u64);
+        assert_eq!(a % Decimal::from(2), Decimal::from_percent(150));
+        assert_eq!(Rate::from_percent(250) % Rate::one(), Rate::half());
+        assert_eq!(a % Rate::half(), Decimal::zero());
+        assert_eq!(
+            Rate::from_percent(250) % Decimal::from(2),
+            Decimal::from_percent(50)
+        );
+        let mut b = Decimal::from(3);
+        b %= &Rate::from_percent(40);
+        assert_eq!(b, Decimal::from_percent(20));
+    }
+
+    #[test]
+    fn test_int_lhs() {
+        assert_eq!(3u64 * Decimal::from(4), Decimal::from(12));
+        assert_eq!(2u8 * &Rate::from_percent(10), Rate::from_percent(20));
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn test_references() {
+        let a = Decimal::from(4);
+        let b = Decimal::from(3);
+        let r = Rate::half();
+        assert_eq!(&a + &b, Decimal::from(7));
+        assert_eq!(&a - b, Decimal::from(1));
+        assert_eq!(a * &r, Decimal::from(2));
+        assert_eq!(&r * &a, Decimal::from(2));
+        assert_eq!(&a / 2u64, Decimal::from(2));
+        assert_eq!(&r + &r, Rate::one());
+    }
+
+    #[test]
+    fn test_assign_ops() {
+        let mut a = Decimal::from(4);
+        a += Decimal::from(2);
+        a -= &Decimal::from(1);
+        a *= Rate::from_percent(200);
+        a /= 5u64;
+        a %= Decimal::from(1);
+        assert_eq!(a, Decimal::zero());
+
+        let mut r = Rate::one();
+        r *= Rate::half();
+        r += &Rate::half();
+        r /= 4u64;
+        assert_eq!(r, Rate::from_percent(25));
+    }
+
+    #[test]
+    #[should_panic(expected = "MathOverflow in `1.000000000000000000 % 0.000000000000000000`")]
+    fn test_rem_by_zero_message() {
+        let _ = Decimal::from(1) % Decimal::zero();
+    }
 }
@@ -1,11 +1,14 @@
-use std::{convert::TryFrom, fmt};
+use std::{cmp::Ordering, convert::TryFrom, fmt, str::FromStr};
 
 use crate::common::*;
 use crate::decimal::*;
+use crate::display::*;
 use crate::error::*;
+use crate::ratio::*;
 
 // Re-export for compatibility with pre 0.1.7 versions
 pub use crate::common::uint::U128;
+use crate::common::uint::U256;
 
 /// Small decimal values, precise to 18 digits
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Eq, Ord)]
@@ -37,7 +40,18 @@ impl Rate {
         U128::from(HALF_WAD)
     }
 
+    /// Create scaled rate from integer value, return an error on overflow
+    pub fn try_from_int(val: impl UnsignedInt) -> Result<Self, DecimalError> {
+        let val = U128::try_from(val.to_u192())?;
+        Ok(Self(
+            Self::wad()
+                .checked_mul(val)
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+
     /// Create scaled decimal from percent value
+    #[cfg(not(feature = "strict"))]
     pub fn from_percent<T>(percent: T) -> Self
     where
         T: Into<U128>,
@@ -46,12 +60,47 @@ impl Rate {
         Self(percent.checked_mul(PERCENT_SCALER.into()).unwrap())
     }
 
+    /// Create scaled decimal from percent value, return an error on overflow
+    pub fn try_from_percent(percent: impl UnsignedInt) -> Result<Self, DecimalError> {
+        let percent = U128::try_from(percent.to_u192())?;
+        Ok(Self(
+            percent
+                .checked_mul(PERCENT_SCALER.into())
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+
+    /// Create scaled decimal from a fractional percent value, e.g. 12.5%
+    ///
+    /// Return an error if the result can't be represented with 18 digits
+    pub fn try_from_percent_decimal(percent: Decimal) -> Result<Self, DecimalError> {
+        Self::try_from(Decimal::try_from_percent_decimal(percent)?)
+    }
+
     /// Create scaled decimal from bps value
+    #[cfg(not(feature = "strict"))]
     pub fn from_bps(bps: impl Into<U128>) -> Self {
         let bps: U128 = bps.into();
         Self(bps.checked_mul(BPS_SCALER.into()).unwrap())
     }
 
+    /// Create scaled decimal from bps value, return an error on overflow
+    pub fn try_from_bps(bps: impl UnsignedInt) -> Result<Self, DecimalError> {
+        let bps = U128::try_from(bps.to_u192())?;
+        Ok(Self(
+            bps.checked_mul(BPS_SCALER.into())
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+
+    /// Create scaled decimal from a fractional bps value, e.g. 2.5 bps
+    ///
+    /// Return an error if the result can't be represented with 18 digits
+    pub fn try_from_bps_decimal(bps: Decimal) -> Result<Self, DecimalError> {
+        Self::try_from(Decimal::try_from_bps_decimal(bps)?)
+    }
+
+    #[cfg(not(feature = "strict"))]
     #[deprecated(since = "0.1.7", note = "please use the generic `from_bps` instead")]
     pub fn from_bps_u64(bps: u64) -> Self {
         Self::from_bps(bps)
@@ -65,6 +114,14 @@ impl Rate {
         T::try_from(self.0).map_err(|_| DecimalError::MathOverflow)
     }
 
+    /// Get percent value from a scaled rate
+    pub fn to_percent<T>(&self) -> Result<T, DecimalError>
+    where
+        T: TryFrom<U128>,
+    {
+        T::try_from(self.0 / PERCENT_SCALER).map_err(|_| DecimalError::MathOverflow)
+    }
+
     /// Create scaled decimal from percent value
     pub fn to_bps<T>(&self) -> Result<T, DecimalError>
     where
@@ -73,6 +130,26 @@ impl Rate {
         T::try_from(self.0 / BPS_SCALER).map_err(|_| DecimalError::MathOverflow)
     }
 
+    /// Get percent value from a scaled rate, keeping the fractional digits
+    pub fn to_percent_decimal(&self) -> Result<Decimal, DecimalError> {
+        Decimal::from(*self).to_percent_decimal()
+    }
+
+    /// Get bps value from a scaled rate, keeping the fractional digits
+    pub fn to_bps_decimal(&self) -> Result<Decimal, DecimalError> {
+        Decimal::from(*self).to_bps_decimal()
+    }
+
+    /// Display as a percentage rounded to `decimals` places, e.g. "5.25%"
+    pub fn display_percent(&self, decimals: usize) -> DisplayPercent {
+        DisplayPercent::new(self.0.into(), decimals)
+    }
+
+    /// Display in bps, e.g. "525 bps"
+    pub fn display_bps(&self) -> DisplayBps {
+        DisplayBps::new(self.0.into())
+    }
+
     /// Create decimal from scaled value
     pub fn from_scaled_val(scaled_val: impl Into<U128>) -> Self {
         Self(scaled_val.into())
@@ -97,26 +174,259 @@ impl Rate {
         self.try_round()
     }
 
+    /// Integer part of the rate
+    pub fn trunc(&self) -> Self {
+        Self(self.0 / Self::wad() * Self::wad())
+    }
+
+    /// Fractional part of the rate
+    pub fn fract(&self) -> Self {
+        Self(self.0 % Self::wad())
+    }
+
+    /// Round to `decimals` fractional digits
+    pub fn try_round_dp(&self, decimals: u32, mode: RoundingMode) -> Result<Self, DecimalError> {
+        if decimals as usize >= SCALE {
+            return Ok(*self);
+        }
+        self.try_round_to_multiple(Self(U128::exp10(SCALE - decimals as usize)), mode)
+    }
+
+    /// Round to a multiple of `step`, e.g. a tick or lot size
+    pub fn try_round_to_multiple(
+        &self,
+        step: Self,
+        mode: RoundingMode,
+    ) -> Result<Self, DecimalError> {
+        if step.0.is_zero() {
+            return Err(DecimalError::InvalidInput);
+        }
+        let steps = self
+            .0
+            .checked_div_rounded(step.0, mode)
+            .ok_or(DecimalError::MathOverflow)?;
+        Ok(Self(
+            steps
+                .checked_mul(step.0)
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+
+    /// Divide with 18 digits of precision, rounding the last digit with `mode`
+    pub fn try_div_rounded(self, rhs: Self, mode: RoundingMode) -> Result<Self, DecimalError> {
+        Ok(Self(
+            self.0
+                .checked_mul(Self::wad())
+                .ok_or(DecimalError::MathOverflow)?
+                .checked_div_rounded(rhs.0, mode)
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+
+    /// Reciprocal `1 / self`, rounding the last digit with `mode`
+    pub fn try_recip(self, mode: RoundingMode) -> Result<Self, DecimalError> {
+        Self::one().try_div_rounded(self, mode)
+    }
+
+    /// Multiply by `numerator / denominator` in one widened step, rounding the last digit with `mode`
+    pub fn try_mul_ratio(self, ratio: &Ratio, mode: RoundingMode) -> Result<Self, DecimalError> {
+        let product = U192::from(self.0) * U192::from(ratio.numerator);
+        let quotient = product
+            .checked_div_rounded(U192::from(ratio.denominator), mode)
+            .ok_or(DecimalError::MathOverflow)?;
+        Ok(Self(U128::try_from(quotient)?))
+    }
+
+    /// Divide into a whole quotient and a remainder, such that `self = q * rhs + r` exactly
+    pub fn try_div_rem(self, rhs: Self) -> Result<(Self, Self), DecimalError> {
+        let quotient = self
+            .0
+            .checked_div(rhs.0)
+            .ok_or(DecimalError::MathOverflow)?;
+        let remainder = self.try_rem(rhs)?;
+        Ok((
+            Self(
+                quotient
+                    .checked_mul(Self::wad())
+                    .ok_or(DecimalError::MathOverflow)?,
+            ),
+            remainder,
+        ))
+    }
+
+    /// Divide, return an error if the quotient can't be represented exactly with 18 digits
+    pub fn try_div_exact(self, rhs: Self) -> Result<Self, DecimalError> {
+        let scaled = self
+            .0
+            .checked_mul(Self::wad())
+            .ok_or(DecimalError::MathOverflow)?;
+        let remainder = scaled
+            .checked_rem(rhs.0)
+            .ok_or(DecimalError::MathOverflow)?;
+        if !remainder.is_zero() {
+            return Err(DecimalError::PrecisionLoss);
+        }
+        Ok(Self(scaled / rhs.0))
+    }
+
+    /// Check if the value is zero
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Check if the value is one
+    pub fn is_one(&self) -> bool {
+        self.0 == Self::wad()
+    }
+
+    /// Absolute difference between two values
+    pub fn abs_diff(self, other: Self) -> Self {
+        if self > other {
+            Self(self.0 - other.0)
+        } else {
+            Self(other.0 - self.0)
+        }
+    }
+
+    /// Restrict the value to `[min, max]`, return an error if `min > max`
+    pub fn try_clamp(self, min: Self, max: Self) -> Result<Self, DecimalError> {
+        if min > max {
+            return Err(DecimalError::InvalidInput);
+        }
+        Ok(self.max(min).min(max))
+    }
+
+    /// Check if two values differ by at most `tolerance`
+    pub fn approx_eq(self, other: Self, tolerance: Self) -> bool {
+        self.abs_diff(other) <= tolerance
+    }
+
+    /// Check if two values differ by at most `bps` relative to the larger one
+    pub fn approx_eq_bps(self, other: Self, bps: u64) -> bool {
+        Decimal::from(self).approx_eq_bps(Decimal::from(other), bps)
+    }
+
+    /// Check if two values differ by at most `ulps` units in the last place (10^-18)
+    pub fn approx_eq_ulps(self, other: Self, ulps: u64) -> bool {
+        self.abs_diff(other).0 <= U128::from(ulps)
+    }
+
+    /// Largest representable value
+    pub fn max_value() -> Self {
+        Self(U128::MAX)
+    }
+
+    /// Checked addition, return `None` on overflow
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.try_add(rhs).ok()
+    }
+
+    /// Checked subtraction, return `None` on underflow
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.try_sub(rhs).ok()
+    }
+
+    /// Checked multiplication, return `None` on overflow
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        self.try_mul(rhs).ok()
+    }
+
+    /// Checked division, return `None` on overflow or divide by zero
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.try_div(rhs).ok()
+    }
+
+    /// Saturating addition, clamp to the max value on overflow
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        self.checked_add(rhs).unwrap_or_else(Self::max_value)
+    }
+
+    /// Saturating subtraction, clamp to zero on underflow
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        self.checked_sub(rhs).unwrap_or_else(Self::zero)
+    }
+
+    /// Saturating multiplication, clamp to the max value on overflow
+    pub fn saturating_mul(self, rhs: Self) -> Self {
+        self.checked_mul(rhs).unwrap_or_else(Self::max_value)
+    }
+
+    /// Wrapping addition, return the result and whether it overflowed
+    pub fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+        let (val, overflow) = self.0.overflowing_add(rhs.0);
+        (Self(val), overflow)
+    }
+
+    /// Wrapping subtraction, return the result and whether it underflowed
+    pub fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+        let (val, overflow) = self.0.overflowing_sub(rhs.0);
+        (Self(val), overflow)
+    }
+
+    /// Wrapping multiplication, return the result and whether it overflowed
+    ///
+    /// On overflow the result is the wrapped scaled product divided by the WAD
+    pub fn overflowing_mul(self, rhs: Self) -> (Self, bool) {
+        match self.try_mul(rhs) {
+            Ok(val) => (val, false),
+            Err(_) => (Self(self.0.overflowing_mul(rhs.0).0 / Self::wad()), true),
+        }
+    }
+
     /// Calculates base^exp
     pub fn try_pow(&self, mut exp: u64) -> Result<Rate, DecimalError> {
         let mut base = *self;
-        let mut ret = if exp % 2 != 0 {
+        let mut ret = if exp % 2 == 1 {
             base
         } else {
             Rate(Self::wad())
         };
 
-        while exp > 0 {
+        // Stop at the highest bit, squaring the base again could overflow for nothing
+        while exp > 1 {
             exp /= 2;
             base = base.try_mul(base)?;
 
-            if exp % 2 != 0 {
+            if exp % 2 == 1 {
                 ret = ret.try_mul(base)?;
             }
         }
 
         Ok(ret)
     }
+
+    /// Approximate `(1 + self)^periods` with the first `terms + 1` terms of the binomial series
+    ///
+    /// With `x = periods * self`, the dropped terms sum to at most
+    /// `x^(terms+1) / (terms+1)! * e^x`, plus up to `terms` ulps from rounding each term down.
+    /// The series is only used while `x < 1`, and 20 terms keep the bound under one ulp for any
+    /// such `x`. Falls back to `try_pow`, which uses rounded-down repeated squaring, when `x >= 1`
+    /// or `periods <= terms`. The result never exceeds the exact value.
+    pub fn try_compound(&self, periods: u64, terms: u64) -> Result<Rate, DecimalError> {
+        let growth = U192::from(self.0)
+            .checked_mul(U192::from(periods))
+            .ok_or(DecimalError::MathOverflow)?;
+        if periods <= terms || growth >= U192::from(WAD) {
+            return Rate::one().try_add(*self)?.try_pow(periods);
+        }
+        let divisor = U256::from(WAD);
+        let mut term = Rate::one();
+        let mut sum = Rate::one();
+        for k in 1..=terms {
+            // term_k = term_{k-1} * r * (n - k + 1) / k
+            let numerator = U256::from(U192::from(term.0))
+                .checked_mul(U256::from(U192::from(self.0)))
+                .and_then(|val| val.checked_mul(U256::from(periods - k + 1)))
+                .ok_or(DecimalError::MathOverflow)?;
+            let next = numerator / (divisor * U256::from(k));
+            term = Rate(U128::try_from(U192::try_from(next)?)?);
+            if term.0.is_zero() {
+                break;
+            }
+            sum = sum.try_add(term)?;
+        }
+        Ok(sum)
+    }
 }
 
 impl fmt::Display for Rate {
@@ -132,6 +442,15 @@ impl fmt::Display for Rate {
     }
 }
 
+impl FromStr for Rate {
+    type Err = DecimalError;
+
+    /// Parse a plain decimal string such as "0.125", up to 18 fractional digits
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s.parse::<Decimal>()?)
+    }
+}
+
 impl TryFrom<Decimal> for Rate {
     type Error = DecimalError;
     fn try_from(decimal: Decimal) -> Result<Self, Self::Error> {
@@ -140,6 +459,8 @@ impl TryFrom<Decimal> for Rate {
 }
 
 impl TryAdd for Rate {
+    type Output = Self;
+
     fn try_add(self, rhs: Self) -> Result<Self, DecimalError> {
         Ok(Self(
             self.0
@@ -150,6 +471,8 @@ impl TryAdd for Rate {
 }
 
 impl TrySub for Rate {
+    type Output = Self;
+
     fn try_sub(self, rhs: Self) -> Result<Self, DecimalError> {
         Ok(Self(
             self.0
@@ -163,6 +486,8 @@ impl<T> TryDiv<T> for Rate
 where
     T: Into<U128>,
 {
+    type Output = Self;
+
     fn try_div(self, rhs: T) -> Result<Self, DecimalError> {
         Ok(Self(
             self.0
@@ -173,6 +498,8 @@ where
 }
 
 impl TryDiv<Rate> for Rate {
+    type Output = Self;
+
     fn try_div(self, rhs: Self) -> Result<Self, DecimalError> {
         Ok(Self(
             self.0
@@ -188,6 +515,8 @@ impl<T> TryMul<T> for Rate
 where
     T: Into<U128>,
 {
+    type Output = Self;
+
     fn try_mul(self, rhs: T) -> Result<Self, DecimalError> {
         Ok(Self(
             self.0
@@ -198,6 +527,8 @@ where
 }
 
 impl TryMul<Rate> for Rate {
+    type Output = Self;
+
     fn try_mul(self, rhs: Self) -> Result<Self, DecimalError> {
         Ok(Self(
             self.0
@@ -209,6 +540,94 @@ impl TryMul<Rate> for Rate {
     }
 }
 
+impl TryAdd<Decimal> for Rate {
+    type Output = Decimal;
+
+    fn try_add(self, rhs: Decimal) -> Result<Decimal, DecimalError> {
+        Decimal::from(self).try_add(rhs)
+    }
+}
+
+impl TrySub<Decimal> for Rate {
+    type Output = Decimal;
+
+    fn try_sub(self, rhs: Decimal) -> Result<Decimal, DecimalError> {
+        Decimal::from(self).try_sub(rhs)
+    }
+}
+
+impl TryMul<Decimal> for Rate {
+    type Output = Decimal;
+
+    fn try_mul(self, rhs: Decimal) -> Result<Decimal, DecimalError> {
+        Decimal::from(self).try_mul(rhs)
+    }
+}
+
+impl TryDiv<Decimal> for Rate {
+    type Output = Decimal;
+
+    fn try_div(self, rhs: Decimal) -> Result<Decimal, DecimalError> {
+        Decimal::from(self).try_div(rhs)
+    }
+}
+
+impl TryRem<Decimal> for Rate {
+    type Output = Decimal;
+
+    fn try_rem(self, rhs: Decimal) -> Result<Decimal, DecimalError> {
+        Decimal::from(self).try_rem(rhs)
+    }
+}
+
+impl TryRem<Rate> for Rate {
+    type Output = Self;
+
+    fn try_rem(self, rhs: Self) -> Result<Self, DecimalError> {
+        Ok(Self(
+            self.0
+                .checked_rem(rhs.0)
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+}
+
+impl TrySum for Rate {
+    fn try_sum<I: Iterator<Item = Self>>(mut iter: I) -> Result<Self, DecimalError> {
+        iter.try_fold(Self::zero(), |acc, x| acc.try_add(x))
+    }
+}
+
+impl<'a> TrySum<&'a Rate> for Rate {
+    fn try_sum<I: Iterator<Item = &'a Self>>(iter: I) -> Result<Self, DecimalError> {
+        Self::try_sum(iter.copied())
+    }
+}
+
+impl TryProduct for Rate {
+    fn try_product<I: Iterator<Item = Self>>(mut iter: I) -> Result<Self, DecimalError> {
+        iter.try_fold(Self::one(), |acc, x| acc.try_mul(x))
+    }
+}
+
+impl<'a> TryProduct<&'a Rate> for Rate {
+    fn try_product<I: Iterator<Item = &'a Self>>(iter: I) -> Result<Self, DecimalError> {
+        Self::try_product(iter.copied())
+    }
+}
+
+impl PartialEq<Decimal> for Rate {
+    fn eq(&self, other: &Decimal) -> bool {
+        other == self
+    }
+}
+
+impl PartialOrd<Decimal> for Rate {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        other.partial_cmp(self).map(Ordering::reverse)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -216,5 +635,245 @@ mod test {
     #[test]
     fn test_pow() {
         assert_eq!(Rate::one(), Rate::one().try_pow(u64::MAX).unwrap());
+        // the base is not squared beyond the highest bit of the exponent
+        let big = Rate::try_from_int(20_000_000_000u64).unwrap();
+        assert_eq!(big.try_pow(1), Ok(big));
+        assert_eq!(
+            Rate::try_from_int(3u8).unwrap().try_pow(5),
+            Rate::try_from_int(243u8)
+        );
+    }
+
+    #[test]
+    fn test_compound() {
+        let rate: Rate = "0.1".parse().unwrap();
+        // few periods are computed exactly
+        assert_eq!(rate.try_compound(3, 3), "1.331".parse::<Rate>());
+        assert_eq!(rate.try_compound(0, 3), Ok(Rate::one()));
+
+        // 1e-9 per slot over a day of slots, x = 0.000216
+        let per_slot = Rate::from_scaled_val(1_000_000_000u64);
+        // (1 + 1e-9)^216000 rounded down to 18 digits
+        let exact: Rate = "1.000216023329571683".parse().unwrap();
+        let approx = per_slot.try_compound(216_000, 3).unwrap();
+        assert!(approx <= exact);
+        assert!(Decimal::from(exact).approx_eq_ulps(Decimal::from(approx), 100));
+        let pow = Rate::one()
+            .try_add(per_slot)
+            .unwrap()
+            .try_pow(216_000)
+            .unwrap();
+        // at least as close as try_pow, which rounds down at every multiplication
+        assert!(approx >= pow);
+        let two_terms = per_slot.try_compound(216_000, 2).unwrap();
+        assert!(two_terms <= approx);
+
+        // x = 0.999, where 20 terms leave only the per-term rounding
+        let per_mille: Rate = "0.001".parse().unwrap();
+        let exact: Rate = "2.714209722513379078".parse().unwrap();
+        let approx = per_mille.try_compound(999, 20).unwrap();
+        assert!(approx <= exact);
+        assert!(Decimal::from(exact).approx_eq_ulps(Decimal::from(approx), 21));
+
+        // x >= 1 is outside the range of the series, so try_pow is used
+        let percent = Rate::try_from_percent(1u64).unwrap();
+        let pow = Rate::one().try_add(percent).unwrap().try_pow(200).unwrap();
+        assert_eq!(percent.try_compound(200, 3), Ok(pow));
+        assert!(pow > Rate::try_from_int(7u64).unwrap());
+        // x = 18.4, where the series would return about 1236 instead of about 1e8 and
+        // try_pow reports the overflow of its intermediate products
+        let ulp = Rate::from_scaled_val(1u64);
+        assert_eq!(
+            ulp.try_compound(u64::MAX, 3),
+            Rate::one().try_add(ulp).unwrap().try_pow(u64::MAX)
+        );
+
+        assert_eq!(
+            Rate::try_from_int(1_000u64)
+                .unwrap()
+                .try_compound(1_000, 10),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_rate_to_percent_bps_decimal() {
+        let x = Rate::try_from_bps(1_250u64).unwrap();
+        assert_eq!(x.to_percent::<u64>().unwrap(), 12);
+        assert_eq!(
+            x.to_percent_decimal().unwrap().to_string(),
+            "12.500000000000000000"
+        );
+        assert_eq!(
+            x.to_bps_decimal().unwrap(),
+            Decimal::try_from_int(1_250u64).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rate_try_from_int() {
+        assert_eq!(Rate::try_from_int(3u8), Rate::try_from_percent(300u64));
+        assert_eq!(Rate::try_from_int(3u128), Rate::try_from_percent(300u64));
+        assert_eq!(
+            Rate::try_from_int(u128::MAX),
+            Err(DecimalError::MathOverflow)
+        );
+        assert_eq!(
+            Rate::try_from_int(U192::MAX),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_rate_try_from_percent_bps() {
+        assert_eq!(Rate::try_from_percent(10u64), "0.1".parse::<Rate>());
+        assert_eq!(Rate::try_from_bps(25u64), "0.0025".parse::<Rate>());
+        assert_eq!(
+            Rate::try_from_bps(U128::MAX),
+            Err(DecimalError::MathOverflow)
+        );
+        assert_eq!(
+            Rate::try_from_percent(U192::MAX),
+            Err(DecimalError::MathOverflow)
+        );
+
+        let pct = Rate::try_from_percent_decimal("12.5".parse().unwrap()).unwrap();
+        assert_eq!(pct, "0.125".parse::<Rate>().unwrap());
+        let bps = Rate::try_from_bps_decimal("0.5".parse().unwrap()).unwrap();
+        assert_eq!(bps, Rate::from_scaled_val(BPS_SCALER / 2));
+        assert_eq!(
+            "1000000000000000000000".parse::<Rate>(),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_rate_checked_saturating_overflowing() {
+        let max = Rate::max_value();
+        assert_eq!(Rate::half().checked_add(Rate::half()), Some(Rate::one()));
+        assert_eq!(max.checked_add(Rate::one()), None);
+        assert_eq!(Rate::half().checked_sub(Rate::one()), None);
+        assert_eq!(Rate::one().checked_div(Rate::zero()), None);
+        assert_eq!(Rate::half().saturating_sub(Rate::one()), Rate::zero());
+        assert_eq!(max.saturating_add(Rate::one()), max);
+        assert_eq!(
+            max.saturating_mul(Rate::try_from_percent(200u64).unwrap()),
+            max
+        );
+        assert_eq!(
+            Rate::half().overflowing_mul(Rate::half()),
+            (Rate::try_from_percent(25u64).unwrap(), false)
+        );
+        assert_eq!(
+            Rate::zero().overflowing_sub(Rate::from_scaled_val(1u64)),
+            (max, true)
+        );
+    }
+
+    #[test]
+    fn test_rate_comparison_utils() {
+        let a = Rate::try_from_percent(30u64).unwrap();
+        let b = Rate::try_from_percent(50u64).unwrap();
+        assert!(Rate::zero().is_zero() && Rate::one().is_one());
+        assert_eq!(a.abs_diff(b), Rate::try_from_percent(20u64).unwrap());
+        assert_eq!(Rate::one().try_clamp(a, b), Ok(b));
+        assert_eq!(Rate::one().try_clamp(b, a), Err(DecimalError::InvalidInput));
+        assert!(a.approx_eq(b, Rate::try_from_percent(20u64).unwrap()));
+        assert!(Rate::one().approx_eq_bps(Rate::try_from_bps(9_950u64).unwrap(), 50));
+        assert!(!Rate::one().approx_eq_bps(Rate::try_from_bps(9_949u64).unwrap(), 50));
+        assert!(a.approx_eq_ulps(a, 0));
+    }
+
+    #[test]
+    fn test_rate_rounding() {
+        let x = "1.2345".parse::<Rate>().unwrap();
+        assert_eq!(x.trunc(), Rate::one());
+        assert_eq!(x.fract(), "0.2345".parse::<Rate>().unwrap());
+        assert_eq!(x.try_round_dp(3, RoundingMode::HalfEven), "1.234".parse());
+        assert_eq!(x.try_round_dp(3, RoundingMode::HalfUp), "1.235".parse());
+        assert_eq!(
+            x.try_round_to_multiple(Rate::try_from_bps(25u64).unwrap(), RoundingMode::Up),
+            "1.235".parse()
+        );
+    }
+
+    #[test]
+    fn test_rate_div_rem_exact() {
+        let a = "0.75".parse::<Rate>().unwrap();
+        let (q, r) = a.try_div_rem(Rate::half()).unwrap();
+        assert_eq!(
+            (q, r),
+            (Rate::one(), Rate::try_from_percent(25u64).unwrap())
+        );
+        assert_eq!(a.try_div_exact(Rate::half()), "1.5".parse());
+        assert_eq!(
+            a.try_rem("0.5".parse::<Decimal>().unwrap()),
+            "0.25".parse::<Decimal>()
+        );
+        assert_eq!(
+            Rate::one().try_div_exact("0.7".parse().unwrap()),
+            Err(DecimalError::PrecisionLoss)
+        );
+    }
+
+    #[test]
+    fn test_rate_recip() {
+        let x = Rate::try_from_percent(300u64).unwrap();
+        assert_eq!(
+            x.try_recip(RoundingMode::HalfUp).unwrap().to_string(),
+            "0.333333333333333333"
+        );
+        assert_eq!(
+            x.try_recip(RoundingMode::Up).unwrap().to_string(),
+            "0.333333333333333334"
+        );
+        assert_eq!(
+            Rate::from_scaled_val(1u64).try_recip(RoundingMode::Down),
+            Ok(Rate::from_scaled_val(WAD as u128 * WAD as u128))
+        );
+        assert_eq!(
+            Rate::zero().try_recip(RoundingMode::Down),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_rate_try_sum_product() {
+        let rates = [
+            Rate::half(),
+            Rate::half(),
+            Rate::try_from_percent(10u64).unwrap(),
+        ];
+        assert_eq!(
+            rates.iter().try_sum(),
+            Ok(Rate::try_from_percent(110u64).unwrap())
+        );
+        assert_eq!(
+            rates.iter().try_product(),
+            Ok(Rate::try_from_bps(250u64).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_try_mul_ratio() {
+        let two_thirds = Ratio::new(2, 3);
+        assert_eq!(
+            Rate::one()
+                .try_mul_ratio(&two_thirds, RoundingMode::HalfUp)
+                .unwrap()
+                .to_string(),
+            "0.666666666666666667"
+        );
+        assert_eq!(
+            Rate::one()
+                .try_mul_ratio(&two_thirds, RoundingMode::Down)
+                .unwrap()
+                .to_string(),
+            "0.666666666666666666"
+        );
+        assert_eq!(
+            Rate::max_value().try_mul_ratio(&Ratio::new(3, 2), RoundingMode::Down),
+            Err(DecimalError::MathOverflow)
+        );
     }
 }
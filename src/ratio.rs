@@ -1,9 +1,24 @@
+use std::{cmp::Ordering, convert::TryFrom, fmt};
+
+use crate::common::uint::{U128, U192};
+use crate::common::*;
+use crate::decimal::*;
+use crate::error::*;
+use crate::rate::*;
+
+/// Exact fraction of two unsigned integers
+///
+/// Equality and ordering compare the values by cross-multiplication, so `1/2 == 2/4`.
+/// Ratios with a zero denominator, which `new` and the public fields allow, are all equal
+/// to each other and greater than any valid ratio, so the order stays total.
+#[derive(Clone, Copy, Debug)]
 pub struct Ratio {
     pub numerator: u64,
     pub denominator: u64,
 }
 
 impl Ratio {
+    /// Create a ratio without validation, prefer `try_new`
     pub fn new(numerator: u64, denominator: u64) -> Self {
         Self {
             numerator,
@@ -11,11 +26,353 @@ impl Ratio {
         }
     }
 
+    /// Create a ratio, return an error on a zero denominator
+    pub fn try_new(numerator: u64, denominator: u64) -> Result<Self, DecimalError> {
+        if denominator == 0 {
+            return Err(DecimalError::InvalidInput);
+        }
+        Ok(Self::new(numerator, denominator))
+    }
+
+    #[cfg(not(feature = "strict"))]
+    #[deprecated(
+        since = "0.2.0",
+        note = "please use `try_mul_amount`, which doesn't panic or truncate"
+    )]
     pub fn mul(&self, amount: u64) -> u64 {
-        (self.numerator as u128)
-            .checked_mul(amount as u128)
-            .unwrap()
-            .checked_div(self.denominator as u128)
-            .unwrap() as u64
+        self.try_mul_amount(amount, RoundingMode::Down).unwrap()
+    }
+
+    /// Multiply an amount by the ratio, return an error if the result doesn't fit a u64
+    pub fn try_mul_amount(&self, amount: u64, mode: RoundingMode) -> Result<u64, DecimalError> {
+        let product = U128::from(self.numerator) * U128::from(amount);
+        let result = product
+            .checked_div_rounded(U128::from(self.denominator), mode)
+            .ok_or(DecimalError::MathOverflow)?;
+        u64::try_from(result).map_err(|_| DecimalError::MathOverflow)
+    }
+
+    /// Convert to a decimal, rounding the last digit with `mode`
+    pub fn try_to_decimal(&self, mode: RoundingMode) -> Result<Decimal, DecimalError> {
+        let scaled = U192::from(self.numerator) * U192::from(WAD);
+        Ok(Decimal(
+            scaled
+                .checked_div_rounded(U192::from(self.denominator), mode)
+                .ok_or(DecimalError::MathOverflow)?,
+        ))
+    }
+
+    /// Convert to a rate, rounding the last digit with `mode`
+    pub fn try_to_rate(&self, mode: RoundingMode) -> Result<Rate, DecimalError> {
+        Rate::try_from(self.try_to_decimal(mode)?)
+    }
+
+    /// Closest ratio to `value` with a denominator of at most `max_denominator`
+    ///
+    /// Uses continued fractions, ties resolve to the smaller denominator.
+    pub fn try_approximate(value: Decimal, max_denominator: u64) -> Result<Self, DecimalError> {
+        if max_denominator == 0 {
+            return Err(DecimalError::InvalidInput);
+        }
+        let divisor = value.0.gcd(U192::from(WAD));
+        let (numerator, denominator) = (value.0 / divisor, U192::from(WAD) / divisor);
+        let max_denominator = U192::from(max_denominator);
+        if denominator <= max_denominator {
+            return Self::try_from_wide(numerator, denominator);
+        }
+
+        let (mut p0, mut q0, mut p1, mut q1) =
+            (U192::zero(), U192::one(), U192::one(), U192::zero());
+        let (mut n, mut d) = (numerator, denominator);
+        loop {
+            let a = n / d;
+            let q2 = q0 + a * q1;
+            if q2 > max_denominator {
+                break;
+            }
+            let p2 = p0 + a * p1;
+            p0 = p1;
+            q0 = q1;
+            p1 = p2;
+            q1 = q2;
+            let rem = n - a * d;
+            n = d;
+            d = rem;
+        }
+        let k = (max_denominator - q0) / q1;
+        let (p, q) = if U192::from(2) * d * (q0 + k * q1) <= denominator {
+            (p1, q1)
+        } else {
+            (p0 + k * p1, q0 + k * q1)
+        };
+        Self::try_from_wide(p, q)
+    }
+
+    /// Equal ratio with the smallest terms
+    pub fn reduce(&self) -> Self {
+        let divisor = U192::from(self.numerator)
+            .gcd(U192::from(self.denominator))
+            .low_u64();
+        if divisor <= 1 {
+            return *self;
+        }
+        Self::new(self.numerator / divisor, self.denominator / divisor)
+    }
+
+    /// Inverse ratio, return an error if the numerator is zero
+    pub fn try_recip(&self) -> Result<Self, DecimalError> {
+        Self::try_new(self.denominator, self.numerator)
+    }
+
+    /// Check if the ratio is below one
+    pub fn is_proper(&self) -> bool {
+        self.numerator < self.denominator
+    }
+
+    /// Reduce wide terms and narrow them, return an error if they don't fit or divide by zero
+    fn try_from_wide(numerator: U192, denominator: U192) -> Result<Self, DecimalError> {
+        if denominator.is_zero() {
+            return Err(DecimalError::MathOverflow);
+        }
+        let divisor = numerator.gcd(denominator);
+        let narrow =
+            |val: U192| u64::try_from(val / divisor).map_err(|_| DecimalError::MathOverflow);
+        Ok(Self::new(narrow(numerator)?, narrow(denominator)?))
+    }
+}
+
+impl TryAdd for Ratio {
+    type Output = Self;
+
+    fn try_add(self, rhs: Self) -> Result<Self, DecimalError> {
+        let numerator = U192::from(self.numerator) * U192::from(rhs.denominator)
+            + U192::from(rhs.numerator) * U192::from(self.denominator);
+        Self::try_from_wide(
+            numerator,
+            U192::from(self.denominator) * U192::from(rhs.denominator),
+        )
+    }
+}
+
+impl TrySub for Ratio {
+    type Output = Self;
+
+    fn try_sub(self, rhs: Self) -> Result<Self, DecimalError> {
+        let numerator = (U192::from(self.numerator) * U192::from(rhs.denominator))
+            .checked_sub(U192::from(rhs.numerator) * U192::from(self.denominator))
+            .ok_or(DecimalError::MathOverflow)?;
+        Self::try_from_wide(
+            numerator,
+            U192::from(self.denominator) * U192::from(rhs.denominator),
+        )
+    }
+}
+
+impl TryMul<Ratio> for Ratio {
+    type Output = Self;
+
+    fn try_mul(self, rhs: Self) -> Result<Self, DecimalError> {
+        Self::try_from_wide(
+            U192::from(self.numerator) * U192::from(rhs.numerator),
+            U192::from(self.denominator) * U192::from(rhs.denominator),
+        )
+    }
+}
+
+impl TryDiv<Ratio> for Ratio {
+    type Output = Self;
+
+    fn try_div(self, rhs: Self) -> Result<Self, DecimalError> {
+        Self::try_from_wide(
+            U192::from(self.numerator) * U192::from(rhs.denominator),
+            U192::from(self.denominator) * U192::from(rhs.numerator),
+        )
+    }
+}
+
+impl PartialEq for Ratio {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ratio {}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ratio {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.denominator == 0, other.denominator == 0) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => {
+                let lhs = self.numerator as u128 * other.denominator as u128;
+                let rhs = other.numerator as u128 * self.denominator as u128;
+                lhs.cmp(&rhs)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(Ratio::try_new(1, 3), Ok(Ratio::new(1, 3)));
+        assert_eq!(Ratio::try_new(1, 0), Err(DecimalError::InvalidInput));
+    }
+
+    #[test]
+    fn test_try_mul_amount() {
+        let third = Ratio::try_new(1, 3).unwrap();
+        assert_eq!(third.try_mul_amount(100, RoundingMode::Down), Ok(33));
+        assert_eq!(third.try_mul_amount(100, RoundingMode::Up), Ok(34));
+        assert_eq!(
+            Ratio::new(3, 2).try_mul_amount(u64::MAX, RoundingMode::Down),
+            Err(DecimalError::MathOverflow)
+        );
+        assert_eq!(
+            Ratio::new(1, 0).try_mul_amount(1, RoundingMode::Down),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_compare() {
+        assert_eq!(Ratio::new(1, 2), Ratio::new(2, 4));
+        assert!(Ratio::new(1, 3) < Ratio::new(1, 2));
+        assert!(Ratio::new(u64::MAX, u64::MAX - 1) < Ratio::new(u64::MAX - 1, u64::MAX - 2));
+        assert_eq!(Ratio::new(2, 3).max(Ratio::new(3, 5)), Ratio::new(2, 3));
+    }
+
+    #[test]
+    fn test_compare_zero_denominator() {
+        let undefined = Ratio::new(0, 0);
+        assert_ne!(undefined, Ratio::new(1, 2));
+        assert_ne!(undefined, Ratio::new(0, 1));
+        assert_eq!(undefined, Ratio::new(3, 0));
+        assert!(undefined > Ratio::new(u64::MAX, 1));
+        let mut ratios = [Ratio::new(1, 0), Ratio::new(1, 2), Ratio::new(0, 1)];
+        ratios.sort();
+        assert_eq!(
+            ratios,
+            [Ratio::new(0, 1), Ratio::new(1, 2), Ratio::new(1, 0)]
+        );
+    }
+
+    #[test]
+    fn test_conversions() {
+        let third = Ratio::new(1, 3);
+        assert_eq!(
+            third
+                .try_to_decimal(RoundingMode::HalfUp)
+                .unwrap()
+                .to_string(),
+            "0.333333333333333333"
+        );
+        assert_eq!(
+            Ratio::new(2, 3)
+                .try_to_rate(RoundingMode::HalfUp)
+                .unwrap()
+                .to_string(),
+            "0.666666666666666667"
+        );
+        assert_eq!(third.to_string(), "1/3");
+        assert_eq!(
+            Ratio::new(1, 0).try_to_decimal(RoundingMode::Down),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_reduce_recip_proper() {
+        let r = Ratio::new(6, 8).reduce();
+        assert_eq!((r.numerator, r.denominator), (3, 4));
+        let zero = Ratio::new(0, 5).reduce();
+        assert_eq!((zero.numerator, zero.denominator), (0, 1));
+        let inv = Ratio::new(3, 4).try_recip().unwrap();
+        assert_eq!((inv.numerator, inv.denominator), (4, 3));
+        assert_eq!(zero.try_recip(), Err(DecimalError::InvalidInput));
+        assert!(Ratio::new(3, 4).is_proper());
+        assert!(!inv.is_proper());
+        assert!(!Ratio::new(1, 1).is_proper());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let treasury = Ratio::new(1, 3);
+        let stakers = Ratio::new(2, 3);
+        let total = treasury.try_add(stakers).unwrap();
+        assert_eq!((total.numerator, total.denominator), (1, 1));
+
+        let diff = Ratio::new(1, 2).try_sub(Ratio::new(1, 3)).unwrap();
+        assert_eq!((diff.numerator, diff.denominator), (1, 6));
+        assert_eq!(
+            Ratio::new(1, 3).try_sub(Ratio::new(1, 2)),
+            Err(DecimalError::MathOverflow)
+        );
+
+        let product = Ratio::new(2, 3).try_mul(Ratio::new(3, 4)).unwrap();
+        assert_eq!((product.numerator, product.denominator), (1, 2));
+        let quotient = Ratio::new(2, 3).try_div(Ratio::new(4, 9)).unwrap();
+        assert_eq!((quotient.numerator, quotient.denominator), (3, 2));
+        assert_eq!(
+            Ratio::new(2, 3).try_div(Ratio::new(0, 1)),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_reduces_wide_terms() {
+        let big = Ratio::new(u64::MAX, u64::MAX - 1);
+        let squared = big.try_mul(big.try_recip().unwrap()).unwrap();
+        assert_eq!((squared.numerator, squared.denominator), (1, 1));
+        assert_eq!(big.try_mul(big), Err(DecimalError::MathOverflow));
+    }
+
+    #[test]
+    fn test_try_approximate() {
+        let pi: Decimal = "3.141592653589793238".parse().unwrap();
+        assert_eq!(Ratio::try_approximate(pi, 10), Ok(Ratio::new(22, 7)));
+        let approx = Ratio::try_approximate(pi, 1000).unwrap();
+        assert_eq!((approx.numerator, approx.denominator), (355, 113));
+
+        let third: Decimal = "0.333333333333333333".parse().unwrap();
+        let approx = Ratio::try_approximate(third, 100).unwrap();
+        assert_eq!((approx.numerator, approx.denominator), (1, 3));
+
+        let exact = Ratio::try_approximate("0.25".parse().unwrap(), 100).unwrap();
+        assert_eq!((exact.numerator, exact.denominator), (1, 4));
+        let zero = Ratio::try_approximate(Decimal::zero(), 5).unwrap();
+        assert_eq!((zero.numerator, zero.denominator), (0, 1));
+        // 0.75 is halfway between 1/1 and 1/2, the smaller denominator wins
+        let tie = Ratio::try_approximate("0.75".parse().unwrap(), 2).unwrap();
+        assert_eq!((tie.numerator, tie.denominator), (1, 1));
+    }
+
+    #[test]
+    fn test_try_approximate_invalid() {
+        assert_eq!(
+            Ratio::try_approximate(Decimal::one(), 0),
+            Err(DecimalError::InvalidInput)
+        );
+        let huge = Decimal::try_from_int(u128::from(u64::MAX) + 1).unwrap();
+        assert_eq!(
+            Ratio::try_approximate(huge, 1),
+            Err(DecimalError::MathOverflow)
+        );
     }
 }
@@ -0,0 +1,334 @@
+use std::convert::TryFrom;
+
+use crate::common::uint::U192;
+use crate::common::*;
+use crate::decimal::*;
+use crate::error::*;
+use crate::rate::*;
+
+/// Seconds in a 365 day year, used to annualize volatility
+pub const SECONDS_PER_YEAR: u64 = 31_536_000;
+
+/// ln(2) rounded down to 18 digits
+const LN_2: u64 = 693_147_180_559_945_309;
+
+/// Smallest value, return an error on an empty slice
+pub fn min(values: &[Decimal]) -> Result<Decimal, DecimalError> {
+    values
+        .iter()
+        .min()
+        .copied()
+        .ok_or(DecimalError::InvalidInput)
+}
+
+/// Largest value, return an error on an empty slice
+pub fn max(values: &[Decimal]) -> Result<Decimal, DecimalError> {
+    values
+        .iter()
+        .max()
+        .copied()
+        .ok_or(DecimalError::InvalidInput)
+}
+
+/// Median of the values, sorting the slice in place
+///
+/// For an even number of values this is the mean of the two middle values, rounded down,
+/// which is the same interpolation `percentile` uses.
+pub fn median(values: &mut [Decimal]) -> Result<Decimal, DecimalError> {
+    percentile(values, Rate::half())
+}
+
+/// Percentile `p` in `[0, 1]` of the values, sorting the slice in place
+///
+/// Uses linear interpolation between the closest ranks: the value at rank `p * (n - 1)`,
+/// where a fractional rank interpolates between its two neighbours, rounded down.
+pub fn percentile(values: &mut [Decimal], p: Rate) -> Result<Decimal, DecimalError> {
+    values.sort_unstable();
+    percentile_sorted(values, p)
+}
+
+/// Spread between the 75th and 25th percentiles, sorting the slice in place
+pub fn interquartile_range(values: &mut [Decimal]) -> Result<Decimal, DecimalError> {
+    values.sort_unstable();
+    let q3 = percentile_sorted(values, Rate::from_scaled_val(3 * WAD / 4))?;
+    let q1 = percentile_sorted(values, Rate::from_scaled_val(WAD / 4))?;
+    q3.try_sub(q1)
+}
+
+/// Population variance of the values
+pub fn variance(values: &[Decimal]) -> Result<Decimal, DecimalError> {
+    signed_variance(&unsigned(values), 0)
+}
+
+/// Sample variance of the values, with Bessel's correction
+pub fn sample_variance(values: &[Decimal]) -> Result<Decimal, DecimalError> {
+    signed_variance(&unsigned(values), 1)
+}
+
+/// Population standard deviation of the values
+pub fn std_dev(values: &[Decimal]) -> Result<Decimal, DecimalError> {
+    Ok(variance(values)?.sqrt())
+}
+
+/// Sample standard deviation of the values, with Bessel's correction
+pub fn sample_std_dev(values: &[Decimal]) -> Result<Decimal, DecimalError> {
+    Ok(sample_variance(values)?.sqrt())
+}
+
+/// Natural log of each price relative to the previous one, as a direction and a magnitude
+pub fn log_returns(prices: &[Decimal]) -> Result<Vec<(Direction, Decimal)>, DecimalError> {
+    prices
+        .windows(2)
+        .map(|pair| {
+            let (prev, cur) = (pair[0], pair[1]);
+            if prev.is_zero() || cur.is_zero() {
+                return Err(DecimalError::InvalidInput);
+            }
+            if cur >= prev {
+                Ok((Direction::Up, ln(cur.try_div(prev)?)?))
+            } else {
+                Ok((Direction::Down, ln(prev.try_div(cur)?)?))
+            }
+        })
+        .collect()
+}
+
+/// Annualized realized volatility of prices sampled every `sample_interval_secs`
+///
+/// Computed as the sample standard deviation of the log returns, scaled by the square root
+/// of the number of samples in a year. Needs at least three prices.
+pub fn annualized_volatility(
+    prices: &[Decimal],
+    sample_interval_secs: u64,
+) -> Result<Decimal, DecimalError> {
+    if sample_interval_secs == 0 {
+        return Err(DecimalError::InvalidInput);
+    }
+    let returns = log_returns(prices)?;
+    let std_dev = signed_variance(&returns, 1)?.sqrt();
+    let periods_per_year = Decimal::try_from_int(SECONDS_PER_YEAR)?
+        .try_div(Decimal::try_from_int(sample_interval_secs)?)?;
+    std_dev.try_mul(periods_per_year.sqrt())
+}
+
+fn unsigned(values: &[Decimal]) -> Vec<(Direction, Decimal)> {
+    values.iter().map(|v| (Direction::Up, *v)).collect()
+}
+
+/// Variance of signed values, dividing the squared deviations by `n - ddof`
+fn signed_variance(values: &[(Direction, Decimal)], ddof: usize) -> Result<Decimal, DecimalError> {
+    if values.len() <= ddof {
+        return Err(DecimalError::InvalidInput);
+    }
+    let mut up = Decimal::zero();
+    let mut down = Decimal::zero();
+    for (direction, magnitude) in values {
+        match direction {
+            Direction::Up => up = up.try_add(*magnitude)?,
+            Direction::Down => down = down.try_add(*magnitude)?,
+        }
+    }
+    let count = U192::from(values.len());
+    let mean_direction = if up >= down {
+        Direction::Up
+    } else {
+        Direction::Down
+    };
+    let mean = Decimal(
+        up.abs_diff(down)
+            .0
+            .checked_div_rounded(count, RoundingMode::HalfEven)
+            .ok_or(DecimalError::MathOverflow)?,
+    );
+    let mut squares = Decimal::zero();
+    for (direction, magnitude) in values {
+        let deviation = if *direction == mean_direction || magnitude.is_zero() {
+            magnitude.abs_diff(mean)
+        } else {
+            magnitude.try_add(mean)?
+        };
+        squares = squares.try_add(deviation.try_mul(deviation)?)?;
+    }
+    squares.try_div(U192::from(values.len() - ddof))
+}
+
+/// Natural log of a value of at least one, accurate to about 10^-16
+fn ln(x: Decimal) -> Result<Decimal, DecimalError> {
+    if x < Decimal::one() {
+        return Err(DecimalError::InvalidInput);
+    }
+    // x = m * 2^k with m in [1, 2)
+    let two = Decimal::try_from_int(2u64)?;
+    let mut m = x;
+    let mut k = 0u64;
+    while m >= two {
+        m = Decimal(m.0 >> 1);
+        k += 1;
+    }
+    // ln(m) = 2 * atanh(z) = 2 * (z + z^3/3 + z^5/5 + ...) with z = (m - 1) / (m + 1) <= 1/3
+    let z = m
+        .try_sub(Decimal::one())?
+        .try_div(m.try_add(Decimal::one())?)?;
+    let z_squared = z.try_mul(z)?;
+    let mut power = z;
+    let mut series = Decimal::zero();
+    let mut n = 1u64;
+    while !power.is_zero() {
+        series = series.try_add(power.try_div(n)?)?;
+        power = power.try_mul(z_squared)?;
+        n += 2;
+    }
+    Decimal::from_scaled_val(LN_2)
+        .try_mul(k)?
+        .try_add(series.try_mul(2u64)?)
+}
+
+fn percentile_sorted(values: &[Decimal], p: Rate) -> Result<Decimal, DecimalError> {
+    if values.is_empty() || p > Rate::one() {
+        return Err(DecimalError::InvalidInput);
+    }
+    let rank = U192::from(p.0)
+        .checked_mul(U192::from(values.len() - 1))
+        .ok_or(DecimalError::MathOverflow)?;
+    let index = usize::try_from(rank / U192::from(WAD)).map_err(|_| DecimalError::MathOverflow)?;
+    let fraction = Decimal(rank % U192::from(WAD));
+    let lower = values[index];
+    if fraction.is_zero() {
+        return Ok(lower);
+    }
+    let upper = values[index + 1];
+    lower.try_add(upper.try_sub(lower)?.try_mul(fraction)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn decimals(values: &[u64]) -> Vec<Decimal> {
+        values
+            .iter()
+            .map(|v| Decimal::try_from_int(*v).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_min_max() {
+        let values = decimals(&[5, 1, 9, 3]);
+        assert_eq!(min(&values), Decimal::try_from_int(1u64));
+        assert_eq!(max(&values), Decimal::try_from_int(9u64));
+        assert_eq!(min(&[]), Err(DecimalError::InvalidInput));
+        assert_eq!(max(&[]), Err(DecimalError::InvalidInput));
+    }
+
+    #[test]
+    fn test_median() {
+        let mut odd = decimals(&[7, 1, 3]);
+        assert_eq!(median(&mut odd), Decimal::try_from_int(3u64));
+        assert_eq!(odd, decimals(&[1, 3, 7]));
+
+        let mut even = decimals(&[4, 1, 2, 3]);
+        assert_eq!(median(&mut even), "2.5".parse());
+
+        let mut single = decimals(&[42]);
+        assert_eq!(median(&mut single), Decimal::try_from_int(42u64));
+        assert_eq!(median(&mut []), Err(DecimalError::InvalidInput));
+
+        // Interpolation rounds down on the last digit
+        let mut tiny = vec![Decimal::zero(), Decimal::from_scaled_val(1u64)];
+        assert_eq!(median(&mut tiny), Ok(Decimal::zero()));
+    }
+
+    #[test]
+    fn test_percentile() {
+        let mut values = decimals(&[10, 40, 20, 30, 50]);
+        assert_eq!(
+            percentile(&mut values, Rate::zero()),
+            Decimal::try_from_int(10u64)
+        );
+        assert_eq!(
+            percentile(&mut values, Rate::one()),
+            Decimal::try_from_int(50u64)
+        );
+        assert_eq!(
+            percentile(&mut values, Rate::try_from_percent(90u64).unwrap()),
+            Decimal::try_from_int(46u64)
+        );
+        assert_eq!(
+            percentile(&mut values, Rate::try_from_percent(101u64).unwrap()),
+            Err(DecimalError::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn test_interquartile_range() {
+        let mut values = decimals(&[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(
+            interquartile_range(&mut values),
+            Decimal::try_from_int(4u64)
+        );
+        let mut values = decimals(&[1, 2, 3, 4]);
+        assert_eq!(interquartile_range(&mut values), "1.5".parse());
+    }
+
+    #[test]
+    fn test_ln() {
+        assert_eq!(ln(Decimal::one()), Ok(Decimal::zero()));
+        let two = Decimal::try_from_int(2u64).unwrap();
+        assert!(ln(two)
+            .unwrap()
+            .approx_eq_ulps(Decimal::from_scaled_val(LN_2), 2));
+        let fifty = Decimal::try_from_int(50u64).unwrap();
+        let expected = "3.912023005428146058".parse::<Decimal>().unwrap();
+        assert!(ln(fifty).unwrap().approx_eq_ulps(expected, 50));
+        assert_eq!(ln("0.5".parse().unwrap()), Err(DecimalError::InvalidInput));
+    }
+
+    #[test]
+    fn test_variance_std_dev() {
+        let values = decimals(&[2, 4, 4, 4, 5, 5, 7, 9]);
+        assert_eq!(variance(&values), Decimal::try_from_int(4u64));
+        assert_eq!(std_dev(&values), Decimal::try_from_int(2u64));
+        assert_eq!(
+            sample_variance(&values).unwrap().to_string(),
+            "4.571428571428571428"
+        );
+        assert_eq!(
+            sample_variance(&decimals(&[1])),
+            Err(DecimalError::InvalidInput)
+        );
+        assert_eq!(variance(&[]), Err(DecimalError::InvalidInput));
+    }
+
+    #[test]
+    fn test_log_returns() {
+        let prices = decimals(&[100, 110, 100]);
+        let returns = log_returns(&prices).unwrap();
+        let expected = "0.095310179804324860".parse::<Decimal>().unwrap();
+        assert_eq!(returns.len(), 2);
+        assert_eq!(returns[0].0, Direction::Up);
+        assert_eq!(returns[1].0, Direction::Down);
+        assert!(returns[0].1.approx_eq_ulps(expected, 10));
+        assert!(returns[1].1.approx_eq_ulps(expected, 10));
+        assert_eq!(
+            log_returns(&decimals(&[1, 0])),
+            Err(DecimalError::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn test_annualized_volatility() {
+        let prices = decimals(&[100, 110, 100, 110]);
+        let daily = 24 * 60 * 60;
+        let vol = annualized_volatility(&prices, daily).unwrap();
+        let expected = "2.102592395633367661".parse::<Decimal>().unwrap();
+        assert!(vol.approx_eq_bps(expected, 1));
+        assert_eq!(
+            annualized_volatility(&prices, 0),
+            Err(DecimalError::InvalidInput)
+        );
+        assert_eq!(
+            annualized_volatility(&prices[..2], daily),
+            Err(DecimalError::InvalidInput)
+        );
+    }
+}
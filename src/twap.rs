@@ -0,0 +1,173 @@
+use crate::common::uint::U192;
+use crate::common::*;
+use crate::decimal::*;
+use crate::error::*;
+
+/// Running sum of price multiplied by time, to derive time-weighted average prices
+///
+/// Take an observation at two points in time and call `twap_since` on the later one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TwapAccumulator {
+    /// Sum of price * seconds up to `last_timestamp`
+    pub cumulative: Decimal,
+    /// Price in effect since `last_timestamp`
+    pub last_price: Decimal,
+    /// Timestamp of the last price update
+    pub last_timestamp: u64,
+}
+
+/// Snapshot of a `TwapAccumulator` at a point in time
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TwapObservation {
+    /// Sum of price * seconds up to `timestamp`
+    pub cumulative: Decimal,
+    /// Time of the snapshot
+    pub timestamp: u64,
+}
+
+impl TwapAccumulator {
+    /// Packed size in bytes
+    pub const LEN: usize = 24 + 24 + 8;
+
+    /// Start accumulating from `price` at `timestamp`
+    pub fn new(price: Decimal, timestamp: u64) -> Self {
+        Self {
+            cumulative: Decimal::zero(),
+            last_price: price,
+            last_timestamp: timestamp,
+        }
+    }
+
+    /// Record a new price at `timestamp`, accruing the previous price up to it
+    pub fn try_update(&mut self, price: Decimal, timestamp: u64) -> Result<(), DecimalError> {
+        self.cumulative = self.observe(timestamp)?.cumulative;
+        self.last_price = price;
+        self.last_timestamp = timestamp;
+        Ok(())
+    }
+
+    /// Snapshot of the accumulator at `timestamp`, which can't precede the last update
+    pub fn observe(&self, timestamp: u64) -> Result<TwapObservation, DecimalError> {
+        let elapsed = timestamp
+            .checked_sub(self.last_timestamp)
+            .ok_or(DecimalError::InvalidInput)?;
+        Ok(TwapObservation {
+            cumulative: self.cumulative.try_add(self.last_price.try_mul(elapsed)?)?,
+            timestamp,
+        })
+    }
+
+    /// Serialize into a slice of exactly `LEN` bytes
+    pub fn pack_into_slice(&self, dst: &mut [u8]) -> Result<(), DecimalError> {
+        if dst.len() != Self::LEN {
+            return Err(DecimalError::InvalidInput);
+        }
+        self.cumulative.0.to_little_endian(&mut dst[0..24]);
+        self.last_price.0.to_little_endian(&mut dst[24..48]);
+        dst[48..56].copy_from_slice(&self.last_timestamp.to_le_bytes());
+        Ok(())
+    }
+
+    /// Deserialize from a slice of exactly `LEN` bytes
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, DecimalError> {
+        if src.len() != Self::LEN {
+            return Err(DecimalError::InvalidInput);
+        }
+        let mut last_timestamp = [0u8; 8];
+        last_timestamp.copy_from_slice(&src[48..56]);
+        Ok(Self {
+            cumulative: Decimal(U192::from_little_endian(&src[0..24])),
+            last_price: Decimal(U192::from_little_endian(&src[24..48])),
+            last_timestamp: u64::from_le_bytes(last_timestamp),
+        })
+    }
+}
+
+impl TwapObservation {
+    /// Packed size in bytes
+    pub const LEN: usize = 24 + 8;
+
+    /// Time-weighted average price between an `earlier` observation and this one
+    pub fn twap_since(&self, earlier: &Self) -> Result<Decimal, DecimalError> {
+        let elapsed = self
+            .timestamp
+            .checked_sub(earlier.timestamp)
+            .filter(|elapsed| *elapsed > 0)
+            .ok_or(DecimalError::InvalidInput)?;
+        self.cumulative
+            .try_sub(earlier.cumulative)?
+            .try_div(elapsed)
+    }
+
+    /// Serialize into a slice of exactly `LEN` bytes
+    pub fn pack_into_slice(&self, dst: &mut [u8]) -> Result<(), DecimalError> {
+        if dst.len() != Self::LEN {
+            return Err(DecimalError::InvalidInput);
+        }
+        self.cumulative.0.to_little_endian(&mut dst[0..24]);
+        dst[24..32].copy_from_slice(&self.timestamp.to_le_bytes());
+        Ok(())
+    }
+
+    /// Deserialize from a slice of exactly `LEN` bytes
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, DecimalError> {
+        if src.len() != Self::LEN {
+            return Err(DecimalError::InvalidInput);
+        }
+        let mut timestamp = [0u8; 8];
+        timestamp.copy_from_slice(&src[24..32]);
+        Ok(Self {
+            cumulative: Decimal(U192::from_little_endian(&src[0..24])),
+            timestamp: u64::from_le_bytes(timestamp),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_twap() {
+        let mut acc = TwapAccumulator::new(Decimal::try_from_int(100u64).unwrap(), 0);
+        let start = acc.observe(0).unwrap();
+        acc.try_update(Decimal::try_from_int(200u64).unwrap(), 30)
+            .unwrap();
+        acc.try_update(Decimal::try_from_int(50u64).unwrap(), 40)
+            .unwrap();
+        let end = acc.observe(100).unwrap();
+        // (100 * 30 + 200 * 10 + 50 * 60) / 100
+        assert_eq!(
+            end.twap_since(&start),
+            Ok(Decimal::try_from_int(80u64).unwrap())
+        );
+
+        let mid = acc.observe(70).unwrap();
+        assert_eq!(
+            end.twap_since(&mid),
+            Ok(Decimal::try_from_int(50u64).unwrap())
+        );
+        assert_eq!(start.twap_since(&end), Err(DecimalError::InvalidInput));
+        assert_eq!(end.twap_since(&end), Err(DecimalError::InvalidInput));
+        assert_eq!(acc.observe(39), Err(DecimalError::InvalidInput));
+    }
+
+    #[test]
+    fn test_pack_unpack() {
+        let mut acc = TwapAccumulator::new(Decimal::try_from_int(3u64).unwrap(), 10);
+        acc.try_update(Decimal::try_from_int(5u64).unwrap(), 20)
+            .unwrap();
+        let mut buf = [0u8; TwapAccumulator::LEN];
+        acc.pack_into_slice(&mut buf).unwrap();
+        assert_eq!(TwapAccumulator::unpack_from_slice(&buf), Ok(acc));
+
+        let obs = acc.observe(25).unwrap();
+        let mut buf = [0u8; TwapObservation::LEN];
+        obs.pack_into_slice(&mut buf).unwrap();
+        assert_eq!(TwapObservation::unpack_from_slice(&buf), Ok(obs));
+        assert_eq!(
+            TwapObservation::unpack_from_slice(&buf[..31]),
+            Err(DecimalError::InvalidInput)
+        );
+    }
+}
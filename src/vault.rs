@@ -0,0 +1,225 @@
+use std::convert::TryFrom;
+
+use crate::common::uint::{U192, U256};
+use crate::common::*;
+use crate::decimal::*;
+use crate::error::*;
+
+/// Exchange rate between vault assets and share tokens
+///
+/// Conversions use `assets * total_shares / total_assets` and the inverse. An empty vault
+/// converts one asset to one share. Virtual assets and shares are added to both totals,
+/// which makes donating assets to inflate the share price expensive for an attacker.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SharePrice {
+    /// Assets held by the vault
+    pub total_assets: Decimal,
+    /// Shares in circulation
+    pub total_shares: u64,
+    /// Assets added to `total_assets` in conversions
+    pub virtual_assets: Decimal,
+    /// Shares added to `total_shares` in conversions
+    pub virtual_shares: u64,
+}
+
+impl SharePrice {
+    /// Create a share price from the vault totals, without virtual offset
+    pub fn new(total_assets: Decimal, total_shares: u64) -> Self {
+        Self {
+            total_assets,
+            total_shares,
+            ..Self::default()
+        }
+    }
+
+    /// Add a virtual offset to both totals
+    ///
+    /// Return an error for virtual shares without virtual assets, which would leave the
+    /// empty vault with shares backed by nothing and make the first deposit fail.
+    pub fn try_with_virtual_offset(
+        self,
+        virtual_assets: Decimal,
+        virtual_shares: u64,
+    ) -> Result<Self, DecimalError> {
+        if virtual_shares > 0 && virtual_assets.is_zero() {
+            return Err(DecimalError::InvalidInput);
+        }
+        Ok(Self {
+            virtual_assets,
+            virtual_shares,
+            ..self
+        })
+    }
+
+    /// Shares worth `assets`, rounding with `mode`
+    ///
+    /// Return an error if shares exist but the vault holds no assets.
+    pub fn try_assets_to_shares(
+        &self,
+        assets: Decimal,
+        mode: RoundingMode,
+    ) -> Result<u64, DecimalError> {
+        let (total_assets, total_shares) = self.try_totals()?;
+        if total_shares == 0 {
+            return assets.try_round_dp(0, mode)?.try_floor();
+        }
+        let shares = U256::from(assets.0)
+            .checked_mul(U256::from(total_shares))
+            .ok_or(DecimalError::MathOverflow)?
+            .checked_div_rounded(U256::from(total_assets.0), mode)
+            .ok_or(DecimalError::MathOverflow)?;
+        u64::try_from(shares).map_err(|_| DecimalError::MathOverflow)
+    }
+
+    /// Assets worth `shares`, rounding the last digit with `mode`
+    pub fn try_shares_to_assets(
+        &self,
+        shares: u64,
+        mode: RoundingMode,
+    ) -> Result<Decimal, DecimalError> {
+        let (total_assets, total_shares) = self.try_totals()?;
+        if total_shares == 0 {
+            return Decimal::try_from_int(shares);
+        }
+        let assets = U256::from(shares)
+            .checked_mul(U256::from(total_assets.0))
+            .ok_or(DecimalError::MathOverflow)?
+            .checked_div_rounded(U256::from(total_shares), mode)
+            .ok_or(DecimalError::MathOverflow)?;
+        Ok(Decimal(U192::try_from(assets)?))
+    }
+
+    /// Shares minted for depositing `assets`, rounded down
+    pub fn try_shares_for_deposit(&self, assets: Decimal) -> Result<u64, DecimalError> {
+        self.try_assets_to_shares(assets, RoundingMode::Down)
+    }
+
+    /// Assets to deposit for minting `shares`, rounded up
+    pub fn try_assets_for_mint(&self, shares: u64) -> Result<Decimal, DecimalError> {
+        self.try_shares_to_assets(shares, RoundingMode::Up)
+    }
+
+    /// Shares burned for withdrawing `assets`, rounded up
+    pub fn try_shares_for_withdraw(&self, assets: Decimal) -> Result<u64, DecimalError> {
+        self.try_assets_to_shares(assets, RoundingMode::Up)
+    }
+
+    /// Assets paid out for redeeming `shares`, rounded down
+    pub fn try_assets_for_redeem(&self, shares: u64) -> Result<Decimal, DecimalError> {
+        self.try_shares_to_assets(shares, RoundingMode::Down)
+    }
+
+    /// Totals including the virtual offset
+    fn try_totals(&self) -> Result<(Decimal, u128), DecimalError> {
+        let total_assets = self.total_assets.try_add(self.virtual_assets)?;
+        let total_shares = u128::from(self.total_shares) + u128::from(self.virtual_shares);
+        Ok((total_assets, total_shares))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_empty_vault() {
+        let vault = SharePrice::default();
+        assert_eq!(
+            vault.try_shares_for_deposit(Decimal::try_from_int(100u64).unwrap()),
+            Ok(100)
+        );
+        assert_eq!(
+            vault.try_assets_for_redeem(100),
+            Ok(Decimal::try_from_int(100u64).unwrap())
+        );
+        let fraction: Decimal = "1.5".parse().unwrap();
+        assert_eq!(vault.try_shares_for_deposit(fraction), Ok(1));
+        assert_eq!(vault.try_shares_for_withdraw(fraction), Ok(2));
+    }
+
+    #[test]
+    fn test_rounds_toward_vault() {
+        // 3 shares backed by 10 assets
+        let vault = SharePrice::new(Decimal::try_from_int(10u64).unwrap(), 3);
+        assert_eq!(
+            vault.try_shares_for_deposit(Decimal::try_from_int(4u64).unwrap()),
+            Ok(1)
+        );
+        assert_eq!(
+            vault.try_shares_for_withdraw(Decimal::try_from_int(4u64).unwrap()),
+            Ok(2)
+        );
+        assert_eq!(
+            vault.try_assets_for_redeem(1).unwrap().to_string(),
+            "3.333333333333333333"
+        );
+        assert_eq!(
+            vault.try_assets_for_mint(1).unwrap().to_string(),
+            "3.333333333333333334"
+        );
+    }
+
+    #[test]
+    fn test_no_assets() {
+        let vault = SharePrice::new(Decimal::zero(), 10);
+        assert_eq!(
+            vault.try_shares_for_deposit(Decimal::try_from_int(1u64).unwrap()),
+            Err(DecimalError::MathOverflow)
+        );
+        assert_eq!(vault.try_assets_for_redeem(10), Ok(Decimal::zero()));
+    }
+
+    #[test]
+    fn test_virtual_offset_against_inflation() {
+        let donated = Decimal::try_from_int(1_000_001u64).unwrap();
+        let deposit = Decimal::try_from_int(1_999_999u64).unwrap();
+        let one = Decimal::one();
+        // the attacker holds the only share and donated a million assets
+        let inflated = SharePrice::new(donated, 1);
+        assert_eq!(inflated.try_shares_for_deposit(deposit), Ok(1));
+
+        let protected = inflated.try_with_virtual_offset(one, 1_000).unwrap();
+        let shares = protected.try_shares_for_deposit(deposit).unwrap();
+        assert_eq!(shares, 2_001);
+        let total = donated.try_add(deposit).unwrap();
+        let after = SharePrice::new(total, 1 + shares)
+            .try_with_virtual_offset(one, 1_000)
+            .unwrap();
+        let redeemed = after.try_assets_for_redeem(shares).unwrap();
+        assert_eq!(redeemed.try_floor::<u64>(), Ok(1_999_667));
+    }
+
+    #[test]
+    fn test_virtual_shares_without_assets() {
+        let deposit = Decimal::try_from_int(100u64).unwrap();
+        assert_eq!(
+            SharePrice::default().try_with_virtual_offset(Decimal::zero(), 1_000),
+            Err(DecimalError::InvalidInput)
+        );
+        // set through the public fields, the first deposit fails instead of panicking
+        let vault = SharePrice {
+            virtual_shares: 1_000,
+            ..SharePrice::default()
+        };
+        assert_eq!(
+            vault.try_shares_for_deposit(deposit),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_conversion_overflow() {
+        let vault = SharePrice::new(Decimal::one(), u64::MAX)
+            .try_with_virtual_offset(Decimal::one(), u64::MAX)
+            .unwrap();
+        assert_eq!(
+            vault.try_shares_for_deposit(Decimal(U192::MAX)),
+            Err(DecimalError::MathOverflow)
+        );
+        let vault = SharePrice::new(Decimal(U192::MAX), 1);
+        assert_eq!(
+            vault.try_assets_for_redeem(u64::MAX),
+            Err(DecimalError::MathOverflow)
+        );
+    }
+}